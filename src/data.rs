@@ -0,0 +1,83 @@
+use crate::{ErrorMsg, GqlResponse};
+use serde::{Deserialize, Deserializer};
+use serde_json::value::Value;
+
+/// State of response `data`, see [`GqlResponse::data_state`]
+///
+/// GraphQL spec distinguishes `"data": null` (error during execution) from
+/// missing `data` (request failed before execution, e.g. validation error),
+/// `Option` from `GqlResponse::data` treats both as `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataState<T> {
+    /// Response has no `data` key
+    #[default]
+    Absent,
+    /// Response has `"data": null`
+    Null,
+    /// Response has data
+    Present(T),
+}
+
+impl<T> DataState<T> {
+    /// Data when present
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            DataState::Present(data) => Some(data),
+            DataState::Absent | DataState::Null => None,
+        }
+    }
+
+    pub fn is_present(&self) -> bool {
+        matches!(self, DataState::Present(_))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DataState<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(data) => DataState::Present(data),
+            None => DataState::Null,
+        })
+    }
+}
+
+/// Wire form of [`GqlResponse`] which keeps `data` state
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub(crate) struct RawResponse<T> {
+    #[serde(default)]
+    data: DataState<T>,
+    errors: Option<Vec<ErrorMsg>>,
+    extensions: Option<Value>,
+}
+
+impl<T> From<RawResponse<T>> for GqlResponse<T> {
+    fn from(raw: RawResponse<T>) -> Self {
+        GqlResponse::new(raw.data, raw.errors, raw.extensions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_state_test() {
+        let state = |body: &str| {
+            serde_json::from_str::<GqlResponse<Value>>(body)
+                .unwrap()
+                .into_data_state()
+        };
+        assert_eq!(state(r#"{ "errors": [] }"#), DataState::Absent);
+        assert_eq!(state(r#"{ "data": null, "errors": [] }"#), DataState::Null);
+        assert_eq!(
+            state(r#"{ "data": { "a": 1 } }"#),
+            DataState::Present(serde_json::json!({ "a": 1 }))
+        );
+
+        let response: GqlResponse<Value> =
+            serde_json::from_str(r#"{ "data": { "a": 1 } }"#).unwrap();
+        assert!(response.data_state().is_present());
+        assert_eq!(response.data_state().into_option().unwrap()["a"], 1);
+    }
+}
@@ -0,0 +1,280 @@
+//! Lightweight analysis of executable GraphQL documents
+
+use crate::lexer::{self, Token, TokenKind};
+
+/// Kind of GraphQL operation, see [`GqlRequest::operation_kind`](crate::GqlRequest::operation_kind)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    /// `query` or shorthand `{ ... }`, side-effect free and safe to retry
+    Query,
+    /// `mutation`
+    Mutation,
+    /// `subscription`, needs streaming transport such as websocket
+    Subscription,
+}
+
+/// Operation definition found in document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Operation<'a> {
+    /// `query`, `mutation` or `subscription`, shorthand `{ ... }` is `query`
+    pub keyword: &'a str,
+    pub name: Option<&'a str>,
+    pub variables: Vec<VariableDefinition<'a>>,
+}
+
+/// Variable declared by operation, e.g. `$book: CreateBook!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct VariableDefinition<'a> {
+    /// Name without `$`
+    pub name: &'a str,
+    /// Type is non-null and there is no default value
+    pub required: bool,
+}
+
+/// Finds all operation definitions, fragments are skipped
+pub(crate) fn operations<'a>(tokens: &[Token<'a>]) -> Vec<Operation<'a>> {
+    let mut operations: Vec<Operation<'a>> = Vec::new();
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+    let mut in_header = false;
+    let mut in_operation_header = false;
+    // Type of last variable definition is being read
+    let mut in_type = false;
+    let mut tokens = tokens
+        .iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .peekable();
+
+    while let Some(token) = tokens.next() {
+        if in_operation_header && depth == 0 && parens == 1 {
+            let operation = operations.last_mut().expect("operation header");
+            match (token.kind, token.text) {
+                (TokenKind::Punctuator, "$") => {
+                    if let Some(next) = tokens.peek().filter(|next| next.kind == TokenKind::Name) {
+                        operation.variables.push(VariableDefinition {
+                            name: next.text,
+                            required: false,
+                        });
+                        in_type = true;
+                    }
+                }
+                (TokenKind::Punctuator, "=") => {
+                    // Variable with default value can be omitted
+                    if let Some(variable) = operation.variables.last_mut() {
+                        variable.required = false;
+                    }
+                    in_type = false;
+                }
+                (TokenKind::Punctuator, "@") | (TokenKind::Punctuator, ")") => in_type = false,
+                (TokenKind::Punctuator, ":") => {}
+                (_, text) if in_type => {
+                    if let Some(variable) = operation.variables.last_mut() {
+                        variable.required = text == "!";
+                    }
+                }
+                _ => {}
+            }
+        }
+        if token.kind == TokenKind::Punctuator {
+            match token.text {
+                "(" => parens += 1,
+                ")" => parens = parens.saturating_sub(1),
+                "{" if parens == 0 => {
+                    if depth == 0 && !in_header {
+                        operations.push(Operation {
+                            keyword: "query",
+                            name: None,
+                            variables: Vec::new(),
+                        });
+                    }
+                    in_header = false;
+                    in_operation_header = false;
+                    depth += 1;
+                }
+                "}" if parens == 0 => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            continue;
+        }
+        if depth > 0 || parens > 0 || in_header || token.kind != TokenKind::Name {
+            continue;
+        }
+        match token.text {
+            "query" | "mutation" | "subscription" => {
+                let name = tokens
+                    .peek()
+                    .filter(|next| next.kind == TokenKind::Name)
+                    .map(|next| next.text);
+                operations.push(Operation {
+                    keyword: token.text,
+                    name,
+                    variables: Vec::new(),
+                });
+                in_header = true;
+                in_operation_header = true;
+            }
+            "fragment" => in_header = true,
+            _ => {}
+        }
+    }
+    operations
+}
+
+/// Kind of operation which would be executed with `operation_name`
+///
+/// Without operation name document must contain exactly one operation.
+pub(crate) fn operation_kind(
+    document: &str,
+    operation_name: Option<&str>,
+) -> Option<OperationKind> {
+    let tokens = lexer::tokenize(document).ok()?;
+    let operations = operations(&tokens);
+    let operation = select(&operations, operation_name)?;
+    match operation.keyword {
+        "mutation" => Some(OperationKind::Mutation),
+        "subscription" => Some(OperationKind::Subscription),
+        _ => Some(OperationKind::Query),
+    }
+}
+
+/// Operation executed with `operation_name`, without name document must
+/// contain exactly one operation
+pub(crate) fn select<'o, 'a>(
+    operations: &'o [Operation<'a>],
+    operation_name: Option<&str>,
+) -> Option<&'o Operation<'a>> {
+    match operation_name {
+        Some(name) => operations
+            .iter()
+            .find(|operation| operation.name == Some(name)),
+        None => match operations {
+            [operation] => Some(operation),
+            _ => None,
+        },
+    }
+}
+
+/// Name of the only operation in document
+///
+/// `None` when document has several operations, anonymous operation or cannot
+/// be tokenized.
+pub(crate) fn single_operation_name(document: &str) -> Option<String> {
+    let tokens = lexer::tokenize(document).ok()?;
+    match operations(&tokens).as_slice() {
+        [operation] => operation.name.map(str::to_string),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    fn find(document: &str) -> Vec<Operation<'_>> {
+        operations(&tokenize(document).unwrap())
+    }
+
+    #[test]
+    fn operations_test() {
+        assert_eq!(
+            find("{ apiVersion }"),
+            vec![Operation {
+                keyword: "query",
+                name: None,
+                variables: vec![]
+            }]
+        );
+        assert_eq!(
+            find(
+                "# comment\nmutation createBook($book: In = { query: 1 }) { query { mutation } }\n\
+                 fragment F on Book { title }\nsubscription { bookAdded { ...F } }"
+            ),
+            vec![
+                Operation {
+                    keyword: "mutation",
+                    name: Some("createBook"),
+                    variables: vec![VariableDefinition {
+                        name: "book",
+                        required: false
+                    }]
+                },
+                Operation {
+                    keyword: "subscription",
+                    name: None,
+                    variables: vec![]
+                },
+            ]
+        );
+        assert!(find("fragment F on Book { title }").is_empty());
+    }
+
+    #[test]
+    fn variable_definitions_test() {
+        let operations = find(
+            "query books($first: Int!, $after: String, $ids: [ID!], $tags: [String]!, \
+             $sort: Sort! = TITLE, $lang: Lang! @deprecated(reason: \"$x\")) { books }",
+        );
+        let variables: Vec<(&str, bool)> = operations[0]
+            .variables
+            .iter()
+            .map(|variable| (variable.name, variable.required))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                ("first", true),
+                ("after", false),
+                ("ids", false),
+                ("tags", true),
+                ("sort", false),
+                ("lang", true),
+            ]
+        );
+        assert_eq!(
+            find("mutation($book: Book!) { a }")[0].variables,
+            vec![VariableDefinition {
+                name: "book",
+                required: true
+            }]
+        );
+        assert!(find("query books { books(first: $first) }")[0]
+            .variables
+            .is_empty());
+    }
+
+    #[test]
+    fn operation_kind_test() {
+        let document = "query A { a } mutation B { b } subscription C { c }";
+        assert_eq!(
+            operation_kind(document, Some("A")),
+            Some(OperationKind::Query)
+        );
+        assert_eq!(
+            operation_kind(document, Some("B")),
+            Some(OperationKind::Mutation)
+        );
+        assert_eq!(
+            operation_kind(document, Some("C")),
+            Some(OperationKind::Subscription)
+        );
+        assert_eq!(operation_kind(document, Some("D")), None);
+        assert_eq!(operation_kind(document, None), None);
+        assert_eq!(operation_kind("{ a }", None), Some(OperationKind::Query));
+        assert_eq!(
+            operation_kind("subscription { a }", None),
+            Some(OperationKind::Subscription)
+        );
+    }
+
+    #[test]
+    fn single_operation_name_test() {
+        assert_eq!(
+            single_operation_name("query Books { books { ...F } } fragment F on Book { title }"),
+            Some("Books".to_string())
+        );
+        assert_eq!(single_operation_name("query { books { title } }"), None);
+        assert_eq!(single_operation_name("query A { a } query B { b }"), None);
+        assert_eq!(single_operation_name("query A { a(x: \"b) }"), None);
+    }
+}
@@ -0,0 +1,168 @@
+/// Defines enum which (de)serializes as GraphQL `SCREAMING_SNAKE_CASE` values
+///
+/// Variant `OnHold` is sent and received as `ON_HOLD`. Last variant can be
+/// marked with `#[other]` to catch values unknown to the client, which keeps
+/// deserialization working when server adds new enum values.
+///
+/// ```
+/// use gqlrequest::gql_enum;
+///
+/// gql_enum! {
+///     #[derive(Debug, Clone, PartialEq)]
+///     pub enum Status {
+///         Active,
+///         OnHold,
+///         #[other]
+///         Unknown(String),
+///     }
+/// }
+///
+/// assert_eq!(serde_json::json!(Status::OnHold), "ON_HOLD");
+/// let status: Status = serde_json::from_str(r#""ARCHIVED""#).unwrap();
+/// assert_eq!(status, Status::Unknown("ARCHIVED".to_string()));
+/// ```
+#[macro_export]
+macro_rules! gql_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $( $(#[doc = $doc:literal])* $variant:ident, )+
+            #[other]
+            $other:ident(String) $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $(#[doc = $doc])* $variant, )+
+            $other(String),
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    $( $name::$variant => f.write_str(
+                        &$crate::__private::screaming_snake_case(stringify!($variant)),
+                    ), )+
+                    $name::$other(value) => f.write_str(value),
+                }
+            }
+        }
+
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                let value: String = $crate::__private::serde::Deserialize::deserialize(deserializer)?;
+                $( if value == $crate::__private::screaming_snake_case(stringify!($variant)) {
+                    return Ok($name::$variant);
+                } )+
+                Ok($name::$other(value))
+            }
+        }
+
+        $crate::gql_enum!(@serialize $name);
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $( $(#[doc = $doc:literal])* $variant:ident ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $(#[doc = $doc])* $variant, )+
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    $( $name::$variant => f.write_str(
+                        &$crate::__private::screaming_snake_case(stringify!($variant)),
+                    ), )+
+                }
+            }
+        }
+
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                let value: String = $crate::__private::serde::Deserialize::deserialize(deserializer)?;
+                $( if value == $crate::__private::screaming_snake_case(stringify!($variant)) {
+                    return Ok($name::$variant);
+                } )+
+                Err($crate::__private::serde::de::Error::custom(format!(
+                    "unknown variant `{}` for enum {}",
+                    value,
+                    stringify!($name)
+                )))
+            }
+        }
+
+        $crate::gql_enum!(@serialize $name);
+    };
+    (@serialize $name:ident) => {
+        impl $crate::__private::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+    };
+}
+
+/// Converts `CamelCase` name to `SCREAMING_SNAKE_CASE`, e.g. `HttpError` to `HTTP_ERROR`
+pub fn screaming_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (index, &character) in chars.iter().enumerate() {
+        if index > 0 && character.is_uppercase() {
+            let previous = chars[index - 1];
+            let next_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.extend(character.to_uppercase());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::gql_enum! {
+        #[derive(Debug, PartialEq)]
+        enum Closed {
+            /// Documented variant
+            Active,
+            HttpError,
+        }
+    }
+
+    #[test]
+    fn screaming_snake_case_test() {
+        assert_eq!(screaming_snake_case("Active"), "ACTIVE");
+        assert_eq!(screaming_snake_case("OnHold"), "ON_HOLD");
+        assert_eq!(screaming_snake_case("HTTPError"), "HTTP_ERROR");
+        assert_eq!(screaming_snake_case("Version2Beta"), "VERSION2_BETA");
+    }
+
+    #[test]
+    fn closed_enum_test() {
+        assert_eq!(serde_json::json!(Closed::HttpError), "HTTP_ERROR");
+        let value: Closed = serde_json::from_str(r#""ACTIVE""#).unwrap();
+        assert_eq!(value, Closed::Active);
+
+        let error = serde_json::from_str::<Closed>(r#""ARCHIVED""#).unwrap_err();
+        assert!(error.to_string().contains("unknown variant `ARCHIVED`"));
+    }
+}
@@ -0,0 +1,396 @@
+use crate::lexer::{self, Token, TokenKind};
+
+const LINE_WIDTH: usize = 80;
+const INDENT: &str = "  ";
+
+/// Formats GraphQL document into canonical form
+///
+/// Every selection is on its own line indented by two spaces, arguments and
+/// variable definitions are separated by `, ` and wrapped one per line when
+/// they do not fit into 80 columns or contain comments. Definitions are
+/// separated by blank line. Documents which cannot be tokenized or have
+/// comments inside argument values are returned unchanged.
+///
+/// ```
+/// use gqlrequest::format_document;
+///
+/// let query = "query Book($id:ID!){book(id:$id){title author{name}}}";
+/// assert_eq!(
+///     format_document(query),
+///     "query Book($id: ID!) {\n  book(id: $id) {\n    title\n    author {\n      name\n    }\n  }\n}\n"
+/// );
+/// ```
+pub fn format_document(document: &str) -> String {
+    match lexer::tokenize(document) {
+        Ok(tokens) => Formatter::default()
+            .format(&tokens)
+            .unwrap_or_else(|| document.to_string()),
+        Err(_) => document.to_string(),
+    }
+}
+
+/// Replaces literals and comments, layout of document is kept
+///
+/// Strings become `""`, numbers `0` and comments `#`. Document which cannot be
+/// tokenized is replaced completely.
+pub(crate) fn obfuscate_literals(document: &str) -> String {
+    let tokens = match lexer::tokenize(document) {
+        Ok(tokens) => tokens,
+        Err(_) => return "# invalid query".to_string(),
+    };
+    let mut output = String::with_capacity(document.len());
+    let mut position = 0;
+    for token in tokens {
+        let placeholder = match token.kind {
+            TokenKind::String | TokenKind::BlockString => "\"\"",
+            TokenKind::Int | TokenKind::Float => "0",
+            TokenKind::Comment => "#",
+            _ => continue,
+        };
+        output.push_str(&document[position..token.offset]);
+        output.push_str(placeholder);
+        position = token.offset + token.text.len();
+    }
+    output.push_str(&document[position..]);
+    output
+}
+
+#[derive(Default)]
+struct Formatter {
+    output: String,
+    depth: usize,
+    after_definition: bool,
+}
+
+impl Formatter {
+    /// `None` when comments cannot be kept
+    fn format(mut self, tokens: &[Token]) -> Option<String> {
+        let mut previous: Option<Token> = None;
+        let mut before_previous: Option<Token> = None;
+        let mut index = 0;
+
+        while index < tokens.len() {
+            let token = tokens[index];
+            index += 1;
+
+            if token.kind == TokenKind::Comment {
+                self.start_line();
+                self.output.push_str(token.text);
+                self.output.push('\n');
+                continue;
+            }
+
+            if token.is_punctuator("{") {
+                if self.line_is_empty() {
+                    self.start_line();
+                } else {
+                    self.output.push(' ');
+                }
+                self.output.push_str("{\n");
+                self.depth += 1;
+            } else if token.is_punctuator("}") {
+                self.depth = self.depth.saturating_sub(1);
+                self.start_line();
+                self.output.push_str("}\n");
+                self.after_definition = self.depth == 0;
+            } else if self.line_is_empty() {
+                self.start_line();
+                self.output.push_str(token.text);
+            } else if self.depth > 0 && starts_selection(previous, before_previous, token) {
+                self.output.push('\n');
+                self.start_line();
+                self.output.push_str(token.text);
+            } else {
+                if needs_space(previous, token, self.depth) {
+                    self.output.push(' ');
+                }
+                self.output.push_str(token.text);
+            }
+
+            let mut last = token;
+            if token.is_punctuator("(") {
+                let end = closing_paren(tokens, index);
+                let items = inline_items(&tokens[index..end])?;
+                let closed = end < tokens.len();
+                self.write_group(&items, closed);
+                if closed {
+                    last = tokens[end];
+                    index = end + 1;
+                } else {
+                    index = end;
+                }
+            }
+            before_previous = previous;
+            previous = Some(last);
+        }
+
+        let mut output = self.output.trim_end().to_string();
+        output.push('\n');
+        Some(output)
+    }
+
+    /// Writes content of parentheses, opening `(` is already written
+    fn write_group(&mut self, items: &[String], closed: bool) {
+        let close = if closed { ")" } else { "" };
+        let single = format!("{}{}", items.join(", "), close);
+        let has_comments = items.iter().any(|item| item.starts_with('#'));
+        if !has_comments && self.column() + single.len() + 2 <= LINE_WIDTH {
+            self.output.push_str(&single);
+            return;
+        }
+        self.output.push('\n');
+        for item in items {
+            self.indent(self.depth + 1);
+            self.output.push_str(item);
+            self.output.push('\n');
+        }
+        self.indent(self.depth);
+        self.output.push_str(close);
+    }
+
+    /// Moves to new line (if needed) and writes indentation
+    fn start_line(&mut self) {
+        if !self.line_is_empty() {
+            self.output.push('\n');
+        }
+        let line_start = self.output.rfind('\n').map_or(0, |index| index + 1);
+        self.output.truncate(line_start);
+        if self.after_definition {
+            self.output.push('\n');
+            self.after_definition = false;
+        }
+        self.indent(self.depth);
+    }
+
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.output.push_str(INDENT);
+        }
+    }
+
+    fn line_is_empty(&self) -> bool {
+        let line_start = self.output.rfind('\n').map_or(0, |index| index + 1);
+        self.output[line_start..].trim().is_empty()
+    }
+
+    fn column(&self) -> usize {
+        let line_start = self.output.rfind('\n').map_or(0, |index| index + 1);
+        self.output[line_start..].chars().count()
+    }
+}
+
+/// Name or spread inside selection set which starts new selection
+fn starts_selection(previous: Option<Token>, before_previous: Option<Token>, token: Token) -> bool {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return false,
+    };
+    let starts = token.kind == TokenKind::Name || token.is_punctuator("...");
+    let after_spread = previous.is_punctuator("...")
+        || (previous.is_name("on") && before_previous.is_some_and(|t| t.is_punctuator("...")));
+    starts
+        && !after_spread
+        && (previous.kind == TokenKind::Name
+            || previous.is_punctuator(")")
+            || previous.is_punctuator("}"))
+}
+
+fn needs_space(previous: Option<Token>, token: Token, depth: usize) -> bool {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return false,
+    };
+    if token.is_punctuator("(") {
+        return depth == 0
+            && (previous.is_name("query")
+                || previous.is_name("mutation")
+                || previous.is_name("subscription"));
+    }
+    if token.is_punctuator(":") || token.is_punctuator("!") {
+        return false;
+    }
+    if previous.is_punctuator("@") || previous.is_punctuator("$") {
+        return false;
+    }
+    !(previous.is_punctuator("...") && token.kind == TokenKind::Name && token.text != "on")
+}
+
+/// Index of `)` closing parentheses opened before `start`, or end of tokens
+fn closing_paren(tokens: &[Token], start: usize) -> usize {
+    let mut nesting = 0;
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        if token.is_punctuator("(") {
+            nesting += 1;
+        } else if token.is_punctuator(")") {
+            if nesting == 0 {
+                return index;
+            }
+            nesting -= 1;
+        }
+    }
+    tokens.len()
+}
+
+/// Renders arguments or variable definitions, one string per item
+///
+/// Comment between items is item of its own, `None` when comment is inside
+/// nested value and would comment out rest of line.
+fn inline_items(tokens: &[Token]) -> Option<Vec<String>> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut nesting = 0usize;
+    let mut previous: Option<Token> = None;
+
+    for &token in tokens {
+        if token.kind == TokenKind::Comment {
+            if nesting > 0 {
+                return None;
+            }
+            if !current.is_empty() {
+                items.push(std::mem::take(&mut current));
+            }
+            items.push(token.text.to_string());
+            continue;
+        }
+        if let Some(previous) = previous {
+            if ends_value(previous) && starts_value(token) {
+                if nesting == 0 {
+                    items.push(std::mem::take(&mut current));
+                } else {
+                    current.push_str(", ");
+                }
+            } else if inline_space(previous, token) {
+                current.push(' ');
+            }
+        }
+        current.push_str(token.text);
+        if token.kind == TokenKind::Punctuator {
+            match token.text {
+                "(" | "[" | "{" => nesting += 1,
+                ")" | "]" | "}" => nesting = nesting.saturating_sub(1),
+                _ => {}
+            }
+        }
+        previous = Some(token);
+    }
+    if !current.is_empty() {
+        items.push(current);
+    }
+    Some(items)
+}
+
+fn ends_value(token: Token) -> bool {
+    match token.kind {
+        TokenKind::Punctuator => matches!(token.text, "]" | "}" | ")" | "!"),
+        _ => true,
+    }
+}
+
+fn starts_value(token: Token) -> bool {
+    match token.kind {
+        TokenKind::Punctuator => matches!(token.text, "$" | "[" | "{"),
+        _ => true,
+    }
+}
+
+fn inline_space(previous: Token, token: Token) -> bool {
+    let closes = matches!(token.text, ":" | "!" | ")" | "]" | "}");
+    let opens = matches!(previous.text, "(" | "[" | "{" | "$" | "@");
+    !(token.kind == TokenKind::Punctuator && closes
+        || previous.kind == TokenKind::Punctuator && opens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_test() {
+        let query = r#"query Book($id:ID!,$n:Int=1){a: book(id:$id){title ...F author{name}} ... on Book @include(if: $x){id}}
+fragment F on Book{isbn}"#;
+        let expected = r#"query Book($id: ID!, $n: Int = 1) {
+  a: book(id: $id) {
+    title
+    ...F
+    author {
+      name
+    }
+  }
+  ... on Book @include(if: $x) {
+    id
+  }
+}
+
+fragment F on Book {
+  isbn
+}
+"#;
+        assert_eq!(format_document(query), expected);
+        assert_eq!(format_document(expected), expected);
+    }
+
+    #[test]
+    fn values_test() {
+        let query = r#"mutation ($ids: [ID!]!) { create(book: {title: "Rocket", tags: ["a" "b"]}, ids: $ids) @deprecated next }"#;
+        let expected = r#"mutation ($ids: [ID!]!) {
+  create(book: {title: "Rocket", tags: ["a", "b"]}, ids: $ids) @deprecated
+  next
+}
+"#;
+        assert_eq!(format_document(query), expected);
+    }
+
+    #[test]
+    fn wrap_test() {
+        let query = "{ createBook(title: \"Rocket Engineering\", author: \"Filip Bucek\", isbn: \"978-3-16-148410-0\") { id } }";
+        let expected = r#"{
+  createBook(
+    title: "Rocket Engineering"
+    author: "Filip Bucek"
+    isbn: "978-3-16-148410-0"
+  ) {
+    id
+  }
+}
+"#;
+        assert_eq!(format_document(query), expected);
+    }
+
+    #[test]
+    fn comment_test() {
+        let query = "# books\n{ books { title # name\n id } }";
+        let expected = "# books\n{\n  books {\n    title\n    # name\n    id\n  }\n}\n";
+        assert_eq!(format_document(query), expected);
+    }
+
+    #[test]
+    fn group_comment_test() {
+        let query = "query Q(\n # first var\n $a: Int) { a }";
+        let expected = "query Q(\n  # first var\n  $a: Int\n) {\n  a\n}\n";
+        assert_eq!(format_document(query), expected);
+        assert_eq!(format_document(expected), expected);
+
+        let query = "{ a(x: { y: 1 # nested\n }) }";
+        assert_eq!(format_document(query), query);
+    }
+
+    #[test]
+    fn obfuscate_test() {
+        let query =
+            "{ user(email: \"filip@example.com\", age: 42, score: 1.5e3) { name } } # secret";
+        assert_eq!(
+            obfuscate_literals(query),
+            "{ user(email: \"\", age: 0, score: 0) { name } } #"
+        );
+        assert_eq!(
+            obfuscate_literals("{ note(text: \"\"\"multi\nline\"\"\") }"),
+            "{ note(text: \"\") }"
+        );
+        assert_eq!(obfuscate_literals("{ a(x: \"open) }"), "# invalid query");
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert_eq!(format_document("{ a ? }"), "{ a ? }");
+    }
+}
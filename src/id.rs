@@ -0,0 +1,159 @@
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// GraphQL `ID` scalar, opaque string
+///
+/// Serializes as plain string. Relay style global IDs (base64 of `Type:id`)
+/// can be created with [`GqlId::global`] and decoded with [`GqlId::decode_global`].
+///
+/// ```
+/// use gqlrequest::GqlId;
+///
+/// let id = GqlId::global("Book", "42");
+/// assert_eq!(id.as_str(), "Qm9vazo0Mg==");
+/// let (type_name, local_id) = id.decode_global().unwrap();
+/// assert_eq!((type_name.as_str(), local_id.as_str()), ("Book", "42"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GqlId(String);
+
+impl GqlId {
+    pub fn new(id: impl Into<String>) -> Self {
+        GqlId(id.into())
+    }
+
+    /// Creates Relay global ID from type name and local id
+    pub fn global(type_name: impl AsRef<str>, id: impl AsRef<str>) -> Self {
+        let global = format!("{}:{}", type_name.as_ref(), id.as_ref());
+        GqlId(encode(global.as_bytes()))
+    }
+
+    /// Decodes Relay global ID into type name and local id
+    pub fn decode_global(&self) -> Result<(String, String)> {
+        let decoded = decode(&self.0)?;
+        let decoded = String::from_utf8(decoded)
+            .map_err(|_| eyre::eyre!("Global ID `{}` is not valid UTF-8", self.0))?;
+        match decoded.split_once(':') {
+            Some((type_name, id)) if !type_name.is_empty() => {
+                Ok((type_name.to_string(), id.to_string()))
+            }
+            _ => Err(eyre::eyre!(
+                "Global ID `{}` does not have `type:id` format",
+                self.0
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for GqlId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for GqlId {
+    fn from(id: String) -> Self {
+        GqlId(id)
+    }
+}
+
+impl From<&str> for GqlId {
+    fn from(id: &str) -> Self {
+        GqlId::new(id)
+    }
+}
+
+impl AsRef<str> for GqlId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Standard base64 with padding
+fn encode(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                output.push(BASE64[(triple >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Standard base64, padding is optional
+fn decode(input: &str) -> Result<Vec<u8>> {
+    let input = input.trim_end_matches('=');
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for character in input.bytes() {
+        let value = BASE64
+            .iter()
+            .position(|&b| b == character)
+            .ok_or_else(|| eyre::eyre!("Global ID `{}` is not valid base64", input))?;
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_test() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg==");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(decode("Zg==").unwrap(), b"f");
+        assert_eq!(decode("Zm8").unwrap(), b"fo");
+        assert!(decode("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn global_id_test() {
+        let id = GqlId::global("User", "a:b");
+        let (type_name, local_id) = id.decode_global().unwrap();
+        assert_eq!(type_name, "User");
+        assert_eq!(local_id, "a:b");
+
+        assert!(GqlId::new("42").decode_global().is_err());
+    }
+
+    #[test]
+    fn serde_test() {
+        let id = GqlId::new("59de6057");
+        assert_eq!(serde_json::json!(id), "59de6057");
+        let id: GqlId = serde_json::from_str(r#""59de6057""#).unwrap();
+        assert_eq!(id.as_str(), "59de6057");
+    }
+}
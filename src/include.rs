@@ -0,0 +1,224 @@
+/// Maximum nesting of `{`, `(` and `[` accepted by [`checked_document`]
+const MAX_NESTING: usize = 64;
+
+/// Embeds GraphQL document from file as `&'static str`
+///
+/// Path is resolved like with [`include_str!`], relative to the file where the
+/// macro is invoked. Document is checked at compile time for characters which
+/// are not valid GraphQL source, lone `.`, unterminated strings, unbalanced
+/// brackets and missing definitions, so broken query files fail the build
+/// instead of the request.
+///
+/// ```
+/// use gqlrequest::{include_gql, GqlRequest};
+///
+/// const CREATE_BOOK: &str = include_gql!(concat!(
+///     env!("CARGO_MANIFEST_DIR"),
+///     "/src/testdata/create_book.graphql"
+/// ));
+///
+/// let request = GqlRequest::with_inferred_operation(CREATE_BOOK);
+/// assert_eq!(request.operation_name.as_deref(), Some("createBook"));
+/// ```
+#[macro_export]
+macro_rules! include_gql {
+    ($path:expr) => {{
+        const DOCUMENT: &str = $crate::__private::checked_document(include_str!($path));
+        DOCUMENT
+    }};
+}
+
+/// Returns `document` when it passes lightweight syntax check, panics otherwise
+///
+/// Evaluated in const context by [`include_gql!`], panic becomes compile error.
+pub const fn checked_document(document: &str) -> &str {
+    let bytes = document.as_bytes();
+    let mut closers = [0u8; MAX_NESTING];
+    let mut depth = 0;
+    let mut has_definition = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' && bytes[i] != b'\r' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'"' if is_block_quote(bytes, i) => {
+                i += 3;
+                loop {
+                    if i >= bytes.len() {
+                        panic!("GraphQL document has unterminated block string");
+                    }
+                    if bytes[i] == b'\\' && is_block_quote(bytes, i + 1) {
+                        i += 4;
+                    } else if is_block_quote(bytes, i) {
+                        i += 3;
+                        break;
+                    } else {
+                        i += 1;
+                    }
+                }
+                continue;
+            }
+            b'"' => {
+                i += 1;
+                loop {
+                    if i >= bytes.len() || bytes[i] == b'\n' || bytes[i] == b'\r' {
+                        panic!("GraphQL document has unterminated string");
+                    }
+                    match bytes[i] {
+                        b'\\' => i += 2,
+                        b'"' => {
+                            i += 1;
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+                continue;
+            }
+            open @ (b'{' | b'(' | b'[') => {
+                if depth == MAX_NESTING {
+                    panic!("GraphQL document is nested too deep");
+                }
+                closers[depth] = match open {
+                    b'{' => b'}',
+                    b'(' => b')',
+                    _ => b']',
+                };
+                depth += 1;
+            }
+            close @ (b'}' | b')' | b']') => {
+                if depth == 0 || closers[depth - 1] != close {
+                    panic!("GraphQL document has unbalanced brackets");
+                }
+                depth -= 1;
+                if depth == 0 && close == b'}' {
+                    has_definition = true;
+                }
+            }
+            b' ' | b'\t' | b'\n' | b'\r' | b',' | b'!' | b'$' | b'&' | b':' | b'=' | b'@'
+            | b'|' => {}
+            b'.' => {
+                if !(i + 2 < bytes.len() && bytes[i + 1] == b'.' && bytes[i + 2] == b'.') {
+                    panic!("GraphQL document has unexpected `.`, expected `...`");
+                }
+                i += 3;
+                continue;
+            }
+            b'_' | b'a'..=b'z' | b'A'..=b'Z' => {
+                while i < bytes.len() && (bytes[i] == b'_' || bytes[i].is_ascii_alphanumeric()) {
+                    i += 1;
+                }
+                continue;
+            }
+            b'-' | b'0'..=b'9' => {
+                i = number_end(bytes, i);
+                continue;
+            }
+            // Byte order mark U+FEFF
+            0xEF if i + 2 < bytes.len() && bytes[i + 1] == 0xBB && bytes[i + 2] == 0xBF => {
+                i += 3;
+                continue;
+            }
+            _ => panic!("GraphQL document has unexpected character"),
+        }
+        i += 1;
+    }
+
+    if depth != 0 {
+        panic!("GraphQL document has unclosed brackets");
+    }
+    if !has_definition {
+        panic!("GraphQL document has no definitions");
+    }
+    document
+}
+
+/// End of number starting at `i`, fraction is consumed only with digit after `.`
+const fn number_end(bytes: &[u8], mut i: usize) -> usize {
+    if bytes[i] == b'-' {
+        i += 1;
+    }
+    i = digits_end(bytes, i);
+    if i + 1 < bytes.len() && bytes[i] == b'.' && bytes[i + 1].is_ascii_digit() {
+        i = digits_end(bytes, i + 1);
+    }
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        i += 1;
+        if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+            i += 1;
+        }
+        i = digits_end(bytes, i);
+    }
+    i
+}
+
+const fn digits_end(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    i
+}
+
+const fn is_block_quote(bytes: &[u8], i: usize) -> bool {
+    i + 2 < bytes.len() && bytes[i] == b'"' && bytes[i + 1] == b'"' && bytes[i + 2] == b'"'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn include_gql_test() {
+        const CREATE_BOOK: &str = include_gql!("testdata/create_book.graphql");
+        assert_eq!(CREATE_BOOK, include_str!("testdata/create_book.graphql"));
+        assert_eq!(
+            checked_document(r#"query { a(text: """ ) \""" """) }"#),
+            r#"query { a(text: """ ) \""" """) }"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unbalanced brackets")]
+    fn unbalanced_test() {
+        checked_document("query { books(first: 1 } }");
+    }
+
+    #[test]
+    #[should_panic(expected = "unterminated string")]
+    fn unterminated_string_test() {
+        checked_document("query { books(title: \"Rocket) }");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected `.`")]
+    fn lone_dot_test() {
+        checked_document("{ a.b }");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected character")]
+    fn unexpected_character_test() {
+        checked_document("{ a ? }");
+    }
+
+    #[test]
+    fn numbers_test() {
+        let document = "{ a(x: -1.5e+3, y: 0, z: [1 2.0]) ...F ... on A { b } }";
+        assert_eq!(checked_document(document), document);
+        assert_eq!(
+            checked_document("\u{feff}{ a(x: \"ž\") }\n# ž"),
+            "\u{feff}{ a(x: \"ž\") }\n# ž"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no definitions")]
+    fn empty_test() {
+        checked_document("# nothing here\n");
+    }
+}
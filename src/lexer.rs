@@ -0,0 +1,371 @@
+//! Minimal GraphQL lexer
+//!
+//! Splits GraphQL document into tokens. Whitespace, commas and byte order mark
+//! are skipped, comments are kept so document can be re-printed.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// One of `! $ & ( ) ... : = @ [ ] { | }`
+    Punctuator,
+    Name,
+    Int,
+    Float,
+    String,
+    BlockString,
+    Comment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset in source
+    pub offset: usize,
+}
+
+impl Token<'_> {
+    pub fn is_punctuator(&self, text: &str) -> bool {
+        self.kind == TokenKind::Punctuator && self.text == text
+    }
+
+    pub fn is_name(&self, text: &str) -> bool {
+        self.kind == TokenKind::Name && self.text == text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LexError {
+    /// Byte offset in source
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+/// Splits document into tokens
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token<'_>>, LexError> {
+    tokens(source).collect()
+}
+
+/// Lazily splits document into tokens, iteration ends after first error
+pub(crate) fn tokens(source: &str) -> Tokens<'_> {
+    Tokens {
+        source,
+        position: 0,
+        failed: false,
+    }
+}
+
+/// Iterator returned by [`tokens`]
+pub(crate) struct Tokens<'a> {
+    source: &'a str,
+    position: usize,
+    failed: bool,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<Token<'a>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let token = next_token(self.source, &mut self.position).transpose()?;
+        self.failed = token.is_err();
+        Some(token)
+    }
+}
+
+/// Reads token at `position`, `None` at end of source
+fn next_token<'a>(source: &'a str, position: &mut usize) -> Result<Option<Token<'a>>, LexError> {
+    let bytes = source.as_bytes();
+    while *position < bytes.len() {
+        let start = *position;
+        let kind = match bytes[start] {
+            b' ' | b'\t' | b'\n' | b'\r' | b',' => {
+                *position += 1;
+                continue;
+            }
+            // Byte order mark U+FEFF
+            0xEF if source[start..].starts_with('\u{feff}') => {
+                *position += '\u{feff}'.len_utf8();
+                continue;
+            }
+            b'#' => {
+                let mut end = start;
+                while end < bytes.len() && bytes[end] != b'\n' && bytes[end] != b'\r' {
+                    end += 1;
+                }
+                *position = end;
+                TokenKind::Comment
+            }
+            b'!' | b'$' | b'&' | b'(' | b')' | b':' | b'=' | b'@' | b'[' | b']' | b'{' | b'|'
+            | b'}' => {
+                *position += 1;
+                TokenKind::Punctuator
+            }
+            b'.' => {
+                if !source[start..].starts_with("...") {
+                    return Err(error(start, "Unexpected `.`, expected `...`"));
+                }
+                *position += 3;
+                TokenKind::Punctuator
+            }
+            b'_' | b'a'..=b'z' | b'A'..=b'Z' => {
+                let mut end = start;
+                while end < bytes.len()
+                    && (bytes[end] == b'_' || bytes[end].is_ascii_alphanumeric())
+                {
+                    end += 1;
+                }
+                *position = end;
+                TokenKind::Name
+            }
+            b'-' | b'0'..=b'9' => {
+                let (end, kind) = number(bytes, start)?;
+                *position = end;
+                kind
+            }
+            b'"' if source[start..].starts_with("\"\"\"") => {
+                *position = block_string(source, start)?;
+                TokenKind::BlockString
+            }
+            b'"' => {
+                *position = string(bytes, start)?;
+                TokenKind::String
+            }
+            _ => return Err(error(start, "Unexpected character")),
+        };
+        return Ok(Some(Token {
+            kind,
+            text: &source[start..*position],
+            offset: start,
+        }));
+    }
+    Ok(None)
+}
+
+/// Lines of `source` with byte offsets of their starts
+///
+/// `\r\n`, `\n` and lone `\r` terminate line, terminators are not included.
+pub(crate) fn lines(source: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut next = Some(0);
+    std::iter::from_fn(move || {
+        let start = next?;
+        let rest = &source[start..];
+        match rest.find(['\r', '\n']) {
+            Some(end) => {
+                let terminator = if rest[end..].starts_with("\r\n") {
+                    2
+                } else {
+                    1
+                };
+                next = Some(start + end + terminator);
+                Some((start, &rest[..end]))
+            }
+            None => {
+                next = None;
+                Some((start, rest))
+            }
+        }
+    })
+}
+
+/// Converts byte offset to 1-based line and column (in characters)
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let (index, start) = lines(source)
+        .map(|(start, _)| start)
+        .take_while(|start| *start <= offset)
+        .enumerate()
+        .last()
+        .unwrap_or((0, 0));
+    (index + 1, source[start..offset].chars().count() + 1)
+}
+
+fn error(offset: usize, message: &'static str) -> LexError {
+    LexError { offset, message }
+}
+
+fn digits(bytes: &[u8], mut position: usize) -> usize {
+    while position < bytes.len() && bytes[position].is_ascii_digit() {
+        position += 1;
+    }
+    position
+}
+
+fn number(bytes: &[u8], start: usize) -> Result<(usize, TokenKind), LexError> {
+    let mut position = start;
+    if bytes[position] == b'-' {
+        position += 1;
+    }
+    let integer_end = digits(bytes, position);
+    if integer_end == position {
+        return Err(error(start, "Invalid number, expected digit"));
+    }
+    if bytes[position] == b'0' && integer_end > position + 1 {
+        return Err(error(start, "Invalid number, unexpected leading zero"));
+    }
+    position = integer_end;
+
+    let mut kind = TokenKind::Int;
+    if position < bytes.len() && bytes[position] == b'.' {
+        let end = digits(bytes, position + 1);
+        if end == position + 1 {
+            return Err(error(start, "Invalid number, expected digit after `.`"));
+        }
+        position = end;
+        kind = TokenKind::Float;
+    }
+    if position < bytes.len() && (bytes[position] == b'e' || bytes[position] == b'E') {
+        position += 1;
+        if position < bytes.len() && (bytes[position] == b'+' || bytes[position] == b'-') {
+            position += 1;
+        }
+        let end = digits(bytes, position);
+        if end == position {
+            return Err(error(start, "Invalid number, expected digit in exponent"));
+        }
+        position = end;
+        kind = TokenKind::Float;
+    }
+    if position < bytes.len()
+        && (bytes[position] == b'.'
+            || bytes[position] == b'_'
+            || bytes[position].is_ascii_alphabetic())
+    {
+        return Err(error(start, "Invalid number"));
+    }
+    Ok((position, kind))
+}
+
+fn string(bytes: &[u8], start: usize) -> Result<usize, LexError> {
+    let mut position = start + 1;
+    while position < bytes.len() {
+        match bytes[position] {
+            b'"' => return Ok(position + 1),
+            b'\n' | b'\r' => break,
+            b'\\' => position += 2,
+            _ => position += 1,
+        }
+    }
+    Err(error(start, "Unterminated string"))
+}
+
+fn block_string(source: &str, start: usize) -> Result<usize, LexError> {
+    let mut position = start + 3;
+    while position < source.len() {
+        let rest = &source[position..];
+        if rest.starts_with("\\\"\"\"") {
+            position += 4;
+        } else if rest.starts_with("\"\"\"") {
+            return Ok(position + 3);
+        } else {
+            position += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    Err(error(start, "Unterminated block string"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn tokenize_test() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("query Book($id: ID!, $n: Int = -1) { book(id: $id) { ...F } } # end"),
+            vec![
+                (Name, "query"),
+                (Name, "Book"),
+                (Punctuator, "("),
+                (Punctuator, "$"),
+                (Name, "id"),
+                (Punctuator, ":"),
+                (Name, "ID"),
+                (Punctuator, "!"),
+                (Punctuator, "$"),
+                (Name, "n"),
+                (Punctuator, ":"),
+                (Name, "Int"),
+                (Punctuator, "="),
+                (Int, "-1"),
+                (Punctuator, ")"),
+                (Punctuator, "{"),
+                (Name, "book"),
+                (Punctuator, "("),
+                (Name, "id"),
+                (Punctuator, ":"),
+                (Punctuator, "$"),
+                (Name, "id"),
+                (Punctuator, ")"),
+                (Punctuator, "{"),
+                (Punctuator, "..."),
+                (Name, "F"),
+                (Punctuator, "}"),
+                (Punctuator, "}"),
+                (Comment, "# end"),
+            ]
+        );
+    }
+
+    #[test]
+    fn values_test() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(r#"1.5e3 0 "a \" b" """block "" \""" end""""#),
+            vec![
+                (Float, "1.5e3"),
+                (Int, "0"),
+                (String, r#""a \" b""#),
+                (BlockString, r#""""block "" \""" end""""#),
+            ]
+        );
+        assert_eq!(kinds("\u{feff}{ a }").len(), 3);
+    }
+
+    #[test]
+    fn tokens_test() {
+        let mut tokens = tokens("{ a ? b }");
+        assert_eq!(tokens.next().unwrap().unwrap().text, "{");
+        assert_eq!(tokens.next().unwrap().unwrap().text, "a");
+        assert_eq!(tokens.next().unwrap().unwrap_err().offset, 4);
+        assert!(tokens.next().is_none());
+    }
+
+    #[test]
+    fn error_test() {
+        assert_eq!(tokenize("{ \"a }").unwrap_err().offset, 2);
+        assert!(tokenize("{ a.b }").is_err());
+        assert!(tokenize("{ a(x: 01) }").is_err());
+        assert!(tokenize("{ a(x: 1a) }").is_err());
+        assert!(tokenize("{ a ? }").is_err());
+        assert!(tokenize(r#"""" never"#).is_err());
+    }
+
+    #[test]
+    fn line_column_test() {
+        let source = "query {\n  book\n}";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 10), (2, 3));
+
+        let source = "query {\r  book\r\n}";
+        assert_eq!(line_column(source, 10), (2, 3));
+        assert_eq!(line_column(source, 16), (3, 1));
+    }
+}
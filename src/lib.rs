@@ -1,7 +1,52 @@
-use eyre::Result;
-use serde::{Deserialize, Serialize};
+use eyre::{Result, WrapErr};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::value::Value;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::Read;
+use std::path::Path;
+
+mod data;
+mod document;
+mod enums;
+mod format;
+mod id;
+mod include;
+mod lexer;
+mod mask;
+mod meta;
+mod nullable;
+mod parts;
+mod payload;
+pub mod prelude;
+mod subscription;
+mod summary;
+mod template;
+mod upload;
+mod validation;
+
+pub use data::DataState;
+pub use document::OperationKind;
+pub use format::format_document;
+pub use id::GqlId;
+pub use mask::MaskMode;
+pub use meta::RequestMeta;
+pub use nullable::Nullable;
+pub use parts::FromDataParts;
+pub use payload::{MutationPayload, UserError};
+pub use subscription::SubscriptionItem;
+pub use summary::ErrorSummary;
+pub use template::GqlTemplate;
+pub use upload::Upload;
+pub use validation::{RequestLimits, ValidationError};
+
+/// Items used by macros, not public API
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::enums::screaming_snake_case;
+    pub use crate::include::checked_document;
+    pub use serde;
+}
 
 /// Request for GraphQL to create JSON requets structure
 ///
@@ -16,23 +61,49 @@ use std::collections::HashMap;
 ///     "query": "mutation createBook($book: createBook!) {\n  createBook(book: $book) {\n    title\n }\n}\n"
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize)]
+///
+/// Variables are kept in a `BTreeMap` so the serialized body is byte-stable
+/// across runs and platforms (usable for hashing and persisted queries).
+///
+/// Captured request bodies can be deserialized back, missing or `null`
+/// `variables` become empty map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GqlRequest {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub operation_name: Option<String>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    pub variables: HashMap<String, Value>,
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub variables: BTreeMap<String, Value>,
     pub query: String,
+    #[serde(
+        default,
+        deserialize_with = "object_or_null",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub extensions: Option<Value>,
+    /// Transport hints such as headers, not part of request body
+    #[serde(skip)]
+    pub meta: RequestMeta,
+    /// Files for multipart request with their variable paths, see
+    /// [`GqlRequest::add_upload`]
+    #[serde(skip)]
+    pub uploads: Vec<(String, Upload)>,
 }
 
 impl GqlRequest {
     /// Cretas new request with only one query
-    pub fn new(query: &str) -> Self {
+    pub fn new(query: impl Into<String>) -> Self {
         GqlRequest {
             operation_name: None,
-            variables: HashMap::new(),
-            query: query.to_string(),
+            variables: BTreeMap::new(),
+            query: query.into(),
+            extensions: None,
+            meta: RequestMeta::default(),
+            uploads: Vec::new(),
         }
     }
 
@@ -42,14 +113,58 @@ impl GqlRequest {
     ///     query: "info()"
     ///     variables: "book": { "title": "Rocket Engineering" }
     /// }
-    pub fn new_with_variable<T: Serialize>(query: &str, variable: &str, object: &T) -> Self {
+    ///
+    /// # Panics
+    ///
+    /// Panics when `object` cannot be serialized to JSON, e.g. map with
+    /// non-string keys. Use [`GqlRequest::add_variable`] to get error instead.
+    pub fn new_with_variable<T: Serialize>(
+        query: impl Into<String>,
+        variable: impl Into<String>,
+        object: &T,
+    ) -> Self {
         GqlRequest {
             operation_name: None,
-            variables: [(variable.to_string(), serde_json::json!(object))]
-                .iter()
-                .cloned()
+            variables: std::iter::once((variable.into(), serde_json::json!(object))).collect(),
+            query: query.into(),
+            extensions: None,
+            meta: RequestMeta::default(),
+            uploads: Vec::new(),
+        }
+    }
+
+    /// Creates new request with several variables at once
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let request = GqlRequest::new_with_variables(
+    ///     "query ($first: Int, $after: String) { books(first: $first, after: $after) { title } }",
+    ///     vec![("first", serde_json::json!(10)), ("after", serde_json::json!("YXJyYXk6MA=="))],
+    /// );
+    /// assert_eq!(request.variables.len(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when any variable cannot be serialized to JSON, e.g. map with
+    /// non-string keys. Use [`GqlRequest::add_variable`] to get error instead.
+    pub fn new_with_variables<I, K, T>(query: impl Into<String>, variables: I) -> Self
+    where
+        I: IntoIterator<Item = (K, T)>,
+        K: Into<String>,
+        T: Serialize,
+    {
+        GqlRequest {
+            operation_name: None,
+            variables: variables
+                .into_iter()
+                .map(|(name, object)| (name.into(), serde_json::json!(object)))
                 .collect(),
-            query: query.to_string(),
+            query: query.into(),
+            extensions: None,
+            meta: RequestMeta::default(),
+            uploads: Vec::new(),
         }
     }
 
@@ -58,30 +173,818 @@ impl GqlRequest {
     /// {
     ///     query: ""
     /// }
-    pub fn new_with_op(operation_name: &str, query: &str) -> Self {
+    pub fn new_with_op(operation_name: impl Into<String>, query: impl Into<String>) -> Self {
         GqlRequest {
-            operation_name: Some(operation_name.to_string()),
-            variables: HashMap::new(),
-            query: query.to_string(),
+            operation_name: Some(operation_name.into()),
+            variables: BTreeMap::new(),
+            query: query.into(),
+            extensions: None,
+            meta: RequestMeta::default(),
+            uploads: Vec::new(),
+        }
+    }
+
+    /// Create new request with variables from struct, each field becomes
+    /// variable
+    ///
+    /// Fails when `variables` does not serialize to JSON object.
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// #[serde(rename_all = "camelCase")]
+    /// struct BooksVariables {
+    ///     first: u32,
+    ///     sort_by: &'static str,
+    /// }
+    ///
+    /// let request = GqlRequest::with_typed_variables(
+    ///     "query books($first: Int, $sortBy: String) { books(first: $first, sortBy: $sortBy) { title } }",
+    ///     "books",
+    ///     &BooksVariables { first: 10, sort_by: "title" },
+    /// )
+    /// .unwrap();
+    /// assert_eq!(request.variables["sortBy"], "title");
+    /// ```
+    pub fn with_typed_variables<V: Serialize>(
+        query: impl Into<String>,
+        operation_name: impl Into<String>,
+        variables: &V,
+    ) -> Result<Self> {
+        let variables = match serde_json::to_value(variables)? {
+            Value::Object(variables) => variables.into_iter().collect(),
+            _ => return Err(eyre::eyre!("GraphQL variables are not JSON object")),
+        };
+        Ok(GqlRequest {
+            variables,
+            ..GqlRequest::new_with_op(operation_name, query)
+        })
+    }
+
+    /// Create new request with operation name taken from query
+    ///
+    /// Name is filled only when document contains exactly one named operation,
+    /// otherwise it stays `None`.
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let request = GqlRequest::with_inferred_operation("mutation createBook { createBook { id } }");
+    /// assert_eq!(request.operation_name.as_deref(), Some("createBook"));
+    /// ```
+    pub fn with_inferred_operation(query: impl Into<String>) -> Self {
+        let query = query.into();
+        GqlRequest {
+            operation_name: document::single_operation_name(&query),
+            ..GqlRequest::new(query)
+        }
+    }
+
+    /// Reads request from `.graphql` / `.gql` file
+    ///
+    /// When document contains exactly one operation, its name is used as
+    /// operation name.
+    ///
+    /// ```no_run
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let request = GqlRequest::from_file("queries/create_book.graphql").unwrap();
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        std::fs::File::open(path)
+            .map_err(eyre::Report::from)
+            .and_then(Self::from_reader)
+            .wrap_err_with(|| format!("Failed to read GraphQL document `{}`", path.display()))
+    }
+
+    /// Reads request from reader, see [`GqlRequest::from_file`]
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut query = String::new();
+        reader.read_to_string(&mut query)?;
+        Ok(GqlRequest {
+            operation_name: document::single_operation_name(&query),
+            ..GqlRequest::from(query)
+        })
+    }
+
+    /// Creates [`GqlRequestBuilder`]
+    pub fn builder() -> GqlRequestBuilder<NoQuery> {
+        GqlRequestBuilder::new()
+    }
+
+    /// Adds variable, replaces previous value with the same name
+    ///
+    /// Fails when `object` cannot be serialized to JSON, e.g. map with
+    /// non-string keys.
+    pub fn add_variable<T: Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        object: &T,
+    ) -> Result<()> {
+        let name = name.into();
+        let json = serde_json::to_value(object)
+            .wrap_err_with(|| format!("Failed to serialize variable `{}`", name))?;
+        self.variables.insert(name, json);
+        Ok(())
+    }
+
+    /// Adds variable which already is JSON value, no serialization needed
+    pub fn add_variable_value(&mut self, name: impl Into<String>, value: Value) {
+        self.variables.insert(name.into(), value);
+    }
+
+    /// Adds variable with explicit `null` value
+    pub fn add_null_variable(&mut self, name: impl Into<String>) {
+        self.variables.insert(name.into(), Value::Null);
+    }
+
+    /// Adds, nulls or removes variable according to [`Nullable`] state
+    pub fn add_nullable_variable<T: Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        value: Nullable<T>,
+    ) -> Result<()> {
+        match value {
+            Nullable::Absent => {
+                self.variables.remove(&name.into());
+                Ok(())
+            }
+            Nullable::Null => {
+                self.add_null_variable(name);
+                Ok(())
+            }
+            Nullable::Value(value) => self.add_variable(name, &value),
+        }
+    }
+
+    /// Removes variable, so it is not sent at all
+    pub fn remove_variable(&mut self, name: &str) -> Option<Value> {
+        self.variables.remove(name)
+    }
+
+    /// Adds file for GraphQL multipart request
+    ///
+    /// `path` is dotted variable path like `file` or `files.0`, value there is
+    /// set to `null` and position is recorded for [`GqlRequest::multipart_map`].
+    /// Upload added again for the same `path` replaces the earlier one.
+    pub fn add_upload(&mut self, path: &str, upload: Upload) -> Result<()> {
+        upload::place(&mut self.variables, path)?;
+        match self
+            .uploads
+            .iter_mut()
+            .find(|(recorded, _)| recorded == path)
+        {
+            Some(entry) => entry.1 = upload,
+            None => self.uploads.push((path.to_string(), upload)),
+        }
+        Ok(())
+    }
+
+    /// `map` field of multipart request, file index to variable paths
+    ///
+    /// Fails when variable of recorded upload is no longer `null`, e.g. after
+    /// [`GqlRequest::set_variables`] replaced it.
+    ///
+    /// ```
+    /// use gqlrequest::{GqlRequest, Upload};
+    ///
+    /// let mut request = GqlRequest::new("mutation($file: Upload!) { upload(file: $file) }");
+    /// request.add_upload("file", Upload::new("a.png", "image/png", vec![])).unwrap();
+    /// assert_eq!(
+    ///     request.multipart_map().unwrap(),
+    ///     serde_json::json!({ "0": ["variables.file"] })
+    /// );
+    /// ```
+    pub fn multipart_map(&self) -> Result<Value> {
+        self.uploads
+            .iter()
+            .enumerate()
+            .map(|(index, (path, _))| {
+                if !upload::is_placeholder(&self.variables, path) {
+                    return Err(eyre::eyre!("Upload variable `{}` is no longer null", path));
+                }
+                Ok((
+                    index.to_string(),
+                    serde_json::json!([format!("variables.{}", path)]),
+                ))
+            })
+            .collect::<Result<serde_json::Map<String, Value>>>()
+            .map(Value::Object)
+    }
+
+    /// Replaces all variables, e.g. with `HashMap<String, Value>` loaded from
+    /// config file
+    pub fn set_variables<I, K>(&mut self, variables: I)
+    where
+        I: IntoIterator<Item = (K, Value)>,
+        K: Into<String>,
+    {
+        self.variables = variables
+            .into_iter()
+            .map(|(name, value)| (name.into(), value))
+            .collect();
+    }
+
+    /// Consuming variant of [`GqlRequest::add_variable`] for chaining
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let request = GqlRequest::new("query books($first: Int) { books(first: $first) { title } }")
+    ///     .with_operation("books")
+    ///     .with_variable("first", &10)
+    ///     .unwrap();
+    /// ```
+    pub fn with_variable<T: Serialize>(
+        mut self,
+        name: impl Into<String>,
+        object: &T,
+    ) -> Result<Self> {
+        self.add_variable(name, object)?;
+        Ok(self)
+    }
+
+    /// Sets operation name, consuming variant for chaining
+    pub fn with_operation(mut self, operation_name: impl Into<String>) -> Self {
+        self.operation_name = Some(operation_name.into());
+        self
+    }
+
+    /// Sets request `extensions`, e.g. for persisted queries or tracing flags
+    pub fn set_extensions(&mut self, extensions: Value) {
+        self.extensions = Some(extensions);
+    }
+
+    /// Serializes request body to JSON string
+    ///
+    /// Fields are in `operationName`, `variables`, `query`, `extensions`
+    /// order, empty variables and missing optional fields are skipped,
+    /// variables are sorted by name.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Serializes request body to JSON bytes, see [`GqlRequest::to_json`]
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Serializes request body to indented JSON string for logs and debugging
+    pub fn to_pretty_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Formats request as `curl` command for reproducing it from logs
+    ///
+    /// Headers from [`RequestMeta`] are added after `headers`, values are
+    /// single-quoted for POSIX shells.
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let curl = GqlRequest::new("{ apiVersion }")
+    ///     .to_curl("https://example.com/graphql", &[("Authorization", "Bearer token")])
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     curl,
+    ///     "curl -X POST 'https://example.com/graphql' -H 'Content-Type: application/json' \
+    ///      -H 'Authorization: Bearer token' --data-raw '{\"query\":\"{ apiVersion }\"}'"
+    /// );
+    /// ```
+    pub fn to_curl(&self, endpoint: &str, headers: &[(&str, &str)]) -> Result<String> {
+        let mut curl = format!(
+            "curl -X POST {} -H {}",
+            shell_quote(endpoint),
+            shell_quote("Content-Type: application/json")
+        );
+        let meta_headers = self
+            .meta
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()));
+        for (name, value) in headers.iter().copied().chain(meta_headers) {
+            curl.push_str(" -H ");
+            curl.push_str(&shell_quote(&format!("{}: {}", name, value)));
+        }
+        curl.push_str(" --data-raw ");
+        curl.push_str(&shell_quote(&self.to_json()?));
+        Ok(curl)
+    }
+
+    /// Encodes request as URL query string for GraphQL over HTTP GET
+    ///
+    /// Contains `query`, `operationName`, and `variables` and `extensions`
+    /// serialized as JSON, missing parts are skipped. Values are percent-encoded,
+    /// only unreserved characters are kept.
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let request = GqlRequest::new_with_variable("query($id: ID) { book(id: $id) { title } }", "id", &"1");
+    /// assert_eq!(
+    ///     request.to_query_string(),
+    ///     "query=query%28%24id%3A%20ID%29%20%7B%20book%28id%3A%20%24id%29%20%7B%20title%20%7D%20%7D\
+    ///      &variables=%7B%22id%22%3A%221%22%7D"
+    /// );
+    /// ```
+    pub fn to_query_string(&self) -> String {
+        let mut pairs = self.query_pairs();
+        if let Some(extensions) = &self.extensions {
+            pairs.push(("extensions", percent_encode(&extensions.to_string())));
+        }
+        join_pairs(&pairs)
+    }
+
+    /// URL opening request in GraphiQL served at `base`
+    ///
+    /// GraphiQL reads `query`, `operationName`, `variables` and `headers` URL
+    /// parameters, headers are taken from [`RequestMeta`]. `extensions` and
+    /// uploads are not exported.
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let url = GqlRequest::builder()
+    ///     .header("Authorization", "Bearer token")
+    ///     .query("{ apiVersion }")
+    ///     .build()
+    ///     .to_graphiql_url("http://localhost:8000/graphiql");
+    /// assert_eq!(
+    ///     url,
+    ///     "http://localhost:8000/graphiql?query=%7B%20apiVersion%20%7D\
+    ///      &headers=%7B%22Authorization%22%3A%22Bearer%20token%22%7D"
+    /// );
+    /// ```
+    pub fn to_graphiql_url(&self, base: &str) -> String {
+        let mut pairs = self.query_pairs();
+        if !self.meta.headers.is_empty() {
+            let headers: serde_json::Map<String, Value> = self
+                .meta
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::from(value.as_str())))
+                .collect();
+            pairs.push((
+                "headers",
+                percent_encode(&Value::Object(headers).to_string()),
+            ));
+        }
+        // Parameters go before fragment
+        let (base, fragment) = match base.find('#') {
+            Some(index) => base.split_at(index),
+            None => (base, ""),
+        };
+        let separator = if base.contains('?') { '&' } else { '?' };
+        format!("{}{}{}{}", base, separator, join_pairs(&pairs), fragment)
+    }
+
+    /// Percent-encoded `query`, `operationName` and `variables` URL parameters
+    fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![("query", percent_encode(&self.query))];
+        if let Some(operation_name) = &self.operation_name {
+            pairs.push(("operationName", percent_encode(operation_name)));
+        }
+        if !self.variables.is_empty() {
+            let variables = serde_json::json!(self.variables).to_string();
+            pairs.push(("variables", percent_encode(&variables)));
+        }
+        pairs
+    }
+
+    /// Kind of operation selected by `operation_name`
+    ///
+    /// Returns `None` when query cannot be tokenized, named operation is not in
+    /// document or request without operation name has several operations.
+    ///
+    /// ```
+    /// use gqlrequest::{GqlRequest, OperationKind};
+    ///
+    /// let request = GqlRequest::new("mutation { createBook { id } }");
+    /// assert_eq!(request.operation_kind(), Some(OperationKind::Mutation));
+    /// ```
+    pub fn operation_kind(&self) -> Option<OperationKind> {
+        document::operation_kind(&self.query, self.operation_name.as_deref())
+    }
+
+    /// Validates request before it is sent
+    ///
+    /// Rejects empty query, control characters in query (except tab, line feed
+    /// and carriage return) and NUL characters in variables.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validation::check_query(&self.query)?;
+        self.variables
+            .iter()
+            .try_for_each(|(name, value)| validation::check_variable(name, value))
+    }
+
+    /// Checks variables against declarations of executed operation
+    ///
+    /// Every non-null variable without default value must be provided and
+    /// not `null`, and no undeclared variable may be set. Not part of
+    /// [`GqlRequest::validate`], as it tokenizes whole query.
+    ///
+    /// ```
+    /// use gqlrequest::{GqlRequest, ValidationError};
+    ///
+    /// let request = GqlRequest::new("mutation($book: CreateBook!) { createBook(book: $book) { id } }");
+    /// assert_eq!(
+    ///     request.validate_variables(),
+    ///     Err(ValidationError::MissingVariable { name: "book".to_string() })
+    /// );
+    /// ```
+    pub fn validate_variables(&self) -> Result<(), ValidationError> {
+        validation::check_declared_variables(
+            &self.query,
+            self.operation_name.as_deref(),
+            &self.variables,
+        )
+    }
+
+    /// Checks that serialized variables do not exceed `limit` bytes
+    ///
+    /// Guards against embedding large blobs which should be uploaded instead.
+    /// Error contains the largest variable and size of each variable.
+    pub fn check_variables_size(&self, limit: usize) -> Result<(), ValidationError> {
+        validation::check_variables_size(&self.variables, limit)
+    }
+
+    /// Returns query with literals replaced by placeholders, for logging
+    ///
+    /// Strings become `""`, numbers `0` and comments `#`, so data embedded in
+    /// query text does not leak into logs while structure is kept.
+    ///
+    /// ```
+    /// use gqlrequest::GqlRequest;
+    ///
+    /// let request = GqlRequest::new(r#"{ user(email: "filip@example.com") { name } }"#);
+    /// assert_eq!(request.obfuscated_query(), r#"{ user(email: "") { name } }"#);
+    /// ```
+    pub fn obfuscated_query(&self) -> String {
+        format::obfuscate_literals(&self.query)
+    }
+
+    /// Checks request received from untrusted client against `limits`
+    ///
+    /// Query length is checked first, so large query is rejected before it is
+    /// tokenized.
+    pub fn check_limits(&self, limits: &RequestLimits) -> Result<(), ValidationError> {
+        validation::check_query_limits(&self.query, limits)?;
+        validation::check_variables_size(&self.variables, limits.max_variables_size)
+    }
+}
+
+impl From<&str> for GqlRequest {
+    fn from(query: &str) -> Self {
+        GqlRequest::new(query)
+    }
+}
+
+impl From<String> for GqlRequest {
+    fn from(query: String) -> Self {
+        GqlRequest::new(query)
+    }
+}
+
+/// Reconstructs request from JSON body, e.g. taken from logs or test fixtures
+impl TryFrom<Value> for GqlRequest {
+    type Error = eyre::Report;
+
+    /// Same as deserializing request body with `serde_json::from_value`
+    fn try_from(value: Value) -> Result<Self> {
+        serde_json::from_value(value)
+            .map_err(|error| eyre::eyre!("Invalid GraphQL request: {}", error))
+    }
+}
+
+/// Marker for [`GqlRequestBuilder`] which does not have query yet
+#[derive(Debug, Clone)]
+pub struct NoQuery;
+
+/// Marker for [`GqlRequestBuilder`] with query set
+#[derive(Debug, Clone)]
+pub struct HasQuery(String);
+
+/// Builder for [`GqlRequest`]
+///
+/// `build()` is available only once query was provided, so missing query is
+/// compile time error.
+///
+/// ```
+/// use gqlrequest::GqlRequestBuilder;
+///
+/// let request = GqlRequestBuilder::new()
+///     .operation_name("createBook")
+///     .query("mutation createBook($title: String!, $pages: Int) { createBook(title: $title, pages: $pages) { title }}")
+///     .variable("title", &"Rocket Engineering")
+///     .variable("pages", &320)
+///     .build();
+/// assert_eq!(request.operation_name.as_deref(), Some("createBook"));
+/// assert_eq!(request.variables.len(), 2);
+/// ```
+///
+/// ```compile_fail
+/// use gqlrequest::GqlRequestBuilder;
+///
+/// let request = GqlRequestBuilder::new().operation_name("apiVersion").build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct GqlRequestBuilder<Q = NoQuery> {
+    operation_name: Option<String>,
+    variables: BTreeMap<String, Value>,
+    extensions: Option<Value>,
+    meta: RequestMeta,
+    query: Q,
+}
+
+impl GqlRequestBuilder<NoQuery> {
+    /// Creates builder without query
+    pub fn new() -> Self {
+        GqlRequestBuilder {
+            operation_name: None,
+            variables: BTreeMap::new(),
+            extensions: None,
+            meta: RequestMeta::default(),
+            query: NoQuery,
+        }
+    }
+
+    /// Sets query, which makes `build()` available
+    pub fn query(self, query: impl Into<String>) -> GqlRequestBuilder<HasQuery> {
+        GqlRequestBuilder {
+            operation_name: self.operation_name,
+            variables: self.variables,
+            extensions: self.extensions,
+            meta: self.meta,
+            query: HasQuery(query.into()),
         }
     }
-    pub fn add_variable<T: Serialize>(&mut self, name: &str, object: &T) -> Result<()> {
-        if self.operation_name.is_none() && !self.variables.is_empty() {
-            Err(eyre::eyre!(
-                "Not possible to add variable when using anonymous query/mutation"
-            ))
-        } else {
-            let json = serde_json::json!(object);
-            self.variables.insert(name.to_string(), json);
-            Ok(())
+}
+
+impl Default for GqlRequestBuilder<NoQuery> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Q> GqlRequestBuilder<Q> {
+    /// Sets operation name
+    pub fn operation_name(mut self, operation_name: impl Into<String>) -> Self {
+        self.operation_name = Some(operation_name.into());
+        self
+    }
+
+    /// Adds variable, variable with the same name is replaced
+    ///
+    /// # Panics
+    ///
+    /// Panics when `object` cannot be serialized to JSON, e.g. map with
+    /// non-string keys. Use [`GqlRequest::add_variable`] to get error instead.
+    pub fn variable<T: Serialize>(mut self, name: impl Into<String>, object: &T) -> Self {
+        self.variables
+            .insert(name.into(), serde_json::json!(object));
+        self
+    }
+
+    /// Adds several variables at once
+    ///
+    /// # Panics
+    ///
+    /// Panics when any variable cannot be serialized to JSON, e.g. map with
+    /// non-string keys. Use [`GqlRequest::add_variable`] to get error instead.
+    pub fn variables<I, K, T>(mut self, variables: I) -> Self
+    where
+        I: IntoIterator<Item = (K, T)>,
+        K: Into<String>,
+        T: Serialize,
+    {
+        self.variables.extend(
+            variables
+                .into_iter()
+                .map(|(name, object)| (name.into(), serde_json::json!(object))),
+        );
+        self
+    }
+
+    /// Sets request `extensions`, e.g. for persisted queries or tracing flags
+    pub fn extensions(mut self, extensions: Value) -> Self {
+        self.extensions = Some(extensions);
+        self
+    }
+
+    /// Adds HTTP header to [`RequestMeta`]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.meta.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets endpoint in [`RequestMeta`]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.meta.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Sets timeout in [`RequestMeta`]
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.meta.timeout = Some(timeout);
+        self
+    }
+}
+
+impl GqlRequestBuilder<HasQuery> {
+    /// Creates request
+    pub fn build(self) -> GqlRequest {
+        GqlRequest {
+            operation_name: self.operation_name,
+            variables: self.variables,
+            query: self.query.0,
+            extensions: self.extensions,
+            meta: self.meta,
+            uploads: Vec::new(),
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(
+    from = "data::RawResponse<T>",
+    bound(deserialize = "T: Deserialize<'de>")
+)]
 pub struct GqlResponse<T> {
     pub data: Option<T>,
     pub errors: Option<Vec<ErrorMsg>>,
+    pub extensions: Option<Value>,
+    /// Body had no `data` key, see [`GqlResponse::data_state`]
+    data_absent: bool,
+}
+
+impl<T> GqlResponse<T> {
+    /// Creates response, e.g. for mocks or transports in other crates
+    ///
+    /// Struct literal is not possible as state of `data` is kept privately in
+    /// sync with [`GqlResponse::data`].
+    ///
+    /// ```
+    /// use gqlrequest::{DataState, GqlResponse};
+    ///
+    /// let response = GqlResponse::new(DataState::Present(42), None, None);
+    /// assert_eq!(response.data, Some(42));
+    /// assert_eq!(GqlResponse::<i32>::new(DataState::Null, None, None).data_state(), DataState::Null);
+    /// ```
+    pub fn new(
+        data: DataState<T>,
+        errors: Option<Vec<ErrorMsg>>,
+        extensions: Option<Value>,
+    ) -> Self {
+        let data_absent = matches!(data, DataState::Absent);
+        GqlResponse {
+            data: data.into_option(),
+            errors,
+            extensions,
+            data_absent,
+        }
+    }
+
+    /// Removes duplicate errors with the same message and extensions
+    ///
+    /// List queries often return the same error for every item, only the first
+    /// one (with its path) is kept.
+    pub fn dedup_errors(&mut self) {
+        if let Some(errors) = self.errors.as_mut() {
+            summary::dedup(errors);
+        }
+    }
+
+    /// Distinguishes `"data": null` from missing `data`
+    ///
+    /// `data` set to `Some` is always `Present`, `None` is `Absent` only when
+    /// response was created without data.
+    pub fn data_state(&self) -> DataState<&T> {
+        match &self.data {
+            Some(data) => DataState::Present(data),
+            None if self.data_absent => DataState::Absent,
+            None => DataState::Null,
+        }
+    }
+
+    /// Consuming variant of [`GqlResponse::data_state`]
+    pub fn into_data_state(self) -> DataState<T> {
+        match self.data {
+            Some(data) => DataState::Present(data),
+            None if self.data_absent => DataState::Absent,
+            None => DataState::Null,
+        }
+    }
+
+    /// Aggregated summary of errors, `None` when there are no errors
+    pub fn error_summary(&self) -> Option<ErrorSummary> {
+        match self.errors.as_deref() {
+            Some(errors) if !errors.is_empty() => Some(ErrorSummary::new(errors)),
+            _ => None,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> GqlResponse<T> {
+    /// Parses response wrapped in custom envelope
+    ///
+    /// `pointer` is JSON pointer to GraphQL response inside the body, e.g.
+    /// `/result` for `{"result": {"data": ...}, "meta": ...}`.
+    pub fn from_envelope(body: &str, pointer: &str) -> Result<Self> {
+        let mut envelope: Value = serde_json::from_str(body)?;
+        let response = envelope
+            .pointer_mut(pointer)
+            .map(Value::take)
+            .ok_or_else(|| eyre::eyre!("Response envelope does not contain `{}`", pointer))?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Parses response and fails on top-level keys other than `data`, `errors`
+    /// and `extensions`
+    ///
+    /// Helps to detect proxies which mangle GraphQL responses, regular
+    /// deserialization ignores unknown keys.
+    pub fn from_str_strict(body: &str) -> Result<Self> {
+        let response: Value = serde_json::from_str(body)?;
+        let object = response
+            .as_object()
+            .ok_or_else(|| eyre::eyre!("GraphQL response is not JSON object"))?;
+        let unknown: Vec<&str> = object
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !matches!(*key, "data" | "errors" | "extensions"))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(eyre::eyre!(
+                "GraphQL response contains unexpected top-level keys: {}",
+                unknown.join(", ")
+            ));
+        }
+        Ok(serde_json::from_value(response)?)
+    }
+}
+
+impl GqlResponse<Value> {
+    /// Masks sensitive values in `data` before response is logged or processed
+    ///
+    /// Paths are relative to `data` with segments separated by `.`, `*` matches
+    /// any field or list index and `**` any number of segments, e.g.
+    /// `users.*.email` or `**.password`.
+    ///
+    /// ```
+    /// use gqlrequest::{GqlResponse, MaskMode};
+    /// use serde_json::Value;
+    ///
+    /// let body = r#"{ "data": { "user": { "name": "Filip", "email": "filip@example.com" } } }"#;
+    /// let mut response: GqlResponse<Value> = serde_json::from_str(body).unwrap();
+    /// response.mask(&["user.email"], MaskMode::Nullify);
+    /// assert_eq!(response.data.unwrap()["user"]["email"], Value::Null);
+    /// ```
+    pub fn mask(&mut self, paths: &[&str], mode: MaskMode) {
+        if let Some(data) = self.data.as_mut() {
+            for path in paths {
+                mask::mask(data, path, mode);
+            }
+        }
+    }
+
+    /// Deserializes several top-level `data` fields into tuple of types
+    ///
+    /// Useful for queries combining unrelated root fields. Missing field is
+    /// deserialized from `null`, so it works for `Option` elements.
+    ///
+    /// ```
+    /// use gqlrequest::GqlResponse;
+    /// use serde::Deserialize;
+    /// use serde_json::Value;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Settings {
+    ///     theme: String,
+    /// }
+    ///
+    /// let body = r#"{ "data": { "user": { "name": "Filip" }, "settings": { "theme": "dark" } } }"#;
+    /// let response: GqlResponse<Value> = serde_json::from_str(body).unwrap();
+    /// let (user, settings) = response
+    ///     .into_parts::<(User, Settings)>(["user", "settings"])
+    ///     .unwrap();
+    /// assert_eq!(user.name, "Filip");
+    /// assert_eq!(settings.theme, "dark");
+    /// ```
+    pub fn into_parts<P: FromDataParts>(self, fields: P::Fields<'_>) -> Result<P> {
+        match self.data {
+            Some(Value::Object(mut data)) => P::from_data_parts(&mut data, fields),
+            Some(_) => Err(eyre::eyre!("Response data is not JSON object")),
+            None => Err(eyre::eyre!("Response does not contain data")),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -92,10 +995,127 @@ pub struct ErrorMsg {
     pub extensions: Option<Value>,
 }
 
+/// Codes from `extensions.code` treated as authentication or authorization errors
+const AUTH_CODES: &[&str] = &["UNAUTHENTICATED", "UNAUTHORIZED", "FORBIDDEN"];
+
+/// Codes from `extensions.code` treated as transient server errors
+const RETRYABLE_CODES: &[&str] = &[
+    "TIMEOUT",
+    "SERVICE_UNAVAILABLE",
+    "RATE_LIMITED",
+    "TOO_MANY_REQUESTS",
+];
+
+impl ErrorMsg {
+    /// Error code from `extensions.code`
+    pub fn code(&self) -> Option<&str> {
+        self.extensions.as_ref()?.get("code")?.as_str()
+    }
+
+    /// Error code is `UNAUTHENTICATED`, `UNAUTHORIZED` or `FORBIDDEN`
+    pub fn is_auth(&self) -> bool {
+        self.code().is_some_and(|code| AUTH_CODES.contains(&code))
+    }
+
+    /// Error code is `TIMEOUT`, `SERVICE_UNAVAILABLE`, `RATE_LIMITED` or
+    /// `TOO_MANY_REQUESTS`, request may succeed when sent again
+    pub fn is_retryable(&self) -> bool {
+        self.code()
+            .is_some_and(|code| RETRYABLE_CODES.contains(&code))
+    }
+}
+
+/// Position in query, `line` and `column` start at 1
 #[derive(Debug, Deserialize)]
 pub struct Location {
-    pub line: i32,
-    pub column: i32,
+    #[serde(deserialize_with = "one_based")]
+    pub line: usize,
+    #[serde(deserialize_with = "one_based")]
+    pub column: usize,
+}
+
+impl Location {
+    /// Maps location back to byte offset in `query`
+    ///
+    /// Column is counted in characters, lines end with `\r\n`, `\n` or lone
+    /// `\r`. Returns `None` when location is outside of the query.
+    ///
+    /// ```
+    /// use gqlrequest::Location;
+    ///
+    /// let location = Location { line: 2, column: 3 };
+    /// assert_eq!(location.to_byte_offset("{\n  named\n}"), Some(4));
+    /// ```
+    pub fn to_byte_offset(&self, query: &str) -> Option<usize> {
+        let (line_start, line) = lexer::lines(query).nth(self.line.checked_sub(1)?)?;
+        match line.char_indices().nth(self.column.checked_sub(1)?) {
+            Some((offset, _)) => Some(line_start + offset),
+            None if line.chars().count() + 1 == self.column => Some(line_start + line.len()),
+            None => None,
+        }
+    }
+}
+
+fn one_based<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = usize::deserialize(deserializer)?;
+    if value == 0 {
+        Err(serde::de::Error::custom(
+            "location line and column start at 1",
+        ))
+    } else {
+        Ok(value)
+    }
+}
+
+/// Joins URL parameters into `name=value&name=value`
+fn join_pairs(pairs: &[(&str, String)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Quotes `value` with single quotes, embedded `'` becomes `'\''`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Request `extensions` must be JSON object when present
+fn object_or_null<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::Object(extensions)) => Ok(Some(Value::Object(extensions))),
+        None => Ok(None),
+        Some(_) => Err(serde::de::Error::custom("extensions is not JSON object")),
+    }
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
 }
 
 #[cfg(test)]
@@ -113,7 +1133,13 @@ mod tests {
         };
 
         let mut request = GqlRequest::new_with_variable("", "test", &test);
-        assert!(request.add_variable("test", &test).is_err())
+        assert!(request.add_variable("other", &test).is_ok());
+        assert_eq!(request.variables.len(), 2);
+
+        let mut map = std::collections::HashMap::new();
+        map.insert(vec![1], 1);
+        let error = request.add_variable("map", &map).unwrap_err();
+        assert_eq!(error.to_string(), "Failed to serialize variable `map`");
     }
 
     #[test]
@@ -197,6 +1223,7 @@ mod tests {
 
         #[derive(Debug, Deserialize)]
         #[serde(rename_all = "camelCase")]
+        #[allow(dead_code)]
         pub struct Sensor {
             pub name: String,
             pub location: Option<String>,
@@ -221,6 +1248,584 @@ mod tests {
         );
     }
 
+    #[test]
+    fn variables_order_test() {
+        let mut request = GqlRequest::new_with_op("createBook", "");
+        request.add_variable("zeta", &1).unwrap();
+        request.add_variable("alpha", &2).unwrap();
+        request.add_variable("mid", &3).unwrap();
+
+        let body = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            body,
+            r#"{"operationName":"createBook","variables":{"alpha":2,"mid":3,"zeta":1},"query":""}"#
+        );
+    }
+
+    #[test]
+    fn new_with_variables_test() {
+        let query =
+            "query ($first: Int, $title: String) { books(first: $first, title: $title) { title }}";
+        let variables: BTreeMap<String, Value> = [
+            ("first".to_string(), serde_json::json!(10)),
+            ("title".to_string(), serde_json::json!("Rocket")),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let request = GqlRequest::new_with_variables(query, variables);
+
+        let expected = serde_json::json!({
+            "variables": {
+                "first": 10,
+                "title": "Rocket",
+            },
+            "query": query,
+        });
+        assert_eq!(serde_json::json!(request), expected);
+    }
+
+    #[test]
+    fn extensions_test() {
+        let query = "query apiVersion { apiVersion }";
+        let extensions = serde_json::json!({
+            "persistedQuery": {
+                "version": 1,
+                "sha256Hash": "ecf4edb46db40b5132295c0291d62fb65d6759a9eedfa4d5d612dd5ec54a6b38",
+            }
+        });
+        let mut request = GqlRequest::new_with_op("apiVersion", query);
+        request.add_variable("first", &10).unwrap();
+        request.set_extensions(extensions.clone());
+
+        let expected = serde_json::json!({
+            "operationName": "apiVersion",
+            "variables": { "first": 10 },
+            "query": query,
+            "extensions": extensions,
+        });
+        assert_eq!(serde_json::json!(request), expected);
+
+        let request = GqlRequest::builder()
+            .query(query)
+            .extensions(extensions.clone())
+            .build();
+        assert_eq!(request.extensions.as_ref(), Some(&extensions));
+        let parsed = GqlRequest::try_from(serde_json::json!(request)).unwrap();
+        assert_eq!(parsed.extensions, Some(extensions));
+    }
+
+    #[test]
+    fn from_test() {
+        let query = "{ apiVersion }";
+        assert_eq!(GqlRequest::from(query).query, query);
+        assert_eq!(GqlRequest::from(query.to_string()).query, query);
+
+        let mut request = GqlRequest::new_with_op(
+            "createBook",
+            "mutation createBook($book: createBook!) { createBook(book: $book) { title }}",
+        );
+        request
+            .add_variable(
+                "book",
+                &serde_json::json!({ "title": "Rocket Engineering" }),
+            )
+            .unwrap();
+        let body = serde_json::json!(request);
+
+        let parsed = GqlRequest::try_from(body.clone()).unwrap();
+        assert_eq!(parsed.operation_name.as_deref(), Some("createBook"));
+        assert_eq!(serde_json::json!(parsed), body);
+
+        let parsed =
+            GqlRequest::try_from(serde_json::json!({ "query": query, "variables": null })).unwrap();
+        assert!(parsed.operation_name.is_none());
+        assert!(parsed.variables.is_empty());
+
+        assert!(GqlRequest::try_from(serde_json::json!({ "operationName": "a" })).is_err());
+        assert!(
+            GqlRequest::try_from(serde_json::json!({ "query": query, "variables": [] })).is_err()
+        );
+        assert!(GqlRequest::try_from(serde_json::json!([])).is_err());
+
+        let body = serde_json::json!({ "query": query, "extensions": 1 });
+        assert!(serde_json::from_value::<GqlRequest>(body.clone()).is_err());
+        assert_eq!(
+            GqlRequest::try_from(body).unwrap_err().to_string(),
+            "Invalid GraphQL request: extensions is not JSON object"
+        );
+    }
+
+    #[test]
+    fn deserialize_test() {
+        let request = GqlRequest::new_with_op(
+            "createBook",
+            "mutation createBook($title: String!) { createBook(title: $title) { id } }",
+        )
+        .with_variable("title", &"Rocket Engineering")
+        .unwrap();
+        let body = serde_json::to_string(&request).unwrap();
+        assert!(body.contains(r#""operationName":"createBook""#));
+        let parsed: GqlRequest = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed, request);
+
+        let parsed: GqlRequest = serde_json::from_str(r#"{ "query": "{ apiVersion }" }"#).unwrap();
+        assert_eq!(parsed, GqlRequest::new("{ apiVersion }"));
+        let parsed: GqlRequest = serde_json::from_str(
+            r#"{ "query": "{ apiVersion }", "variables": null, "operationName": null }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, GqlRequest::new("{ apiVersion }"));
+        assert!(serde_json::from_str::<GqlRequest>(r#"{ "operation_name": "a" }"#).is_err());
+    }
+
+    #[test]
+    fn owned_query_test() {
+        let query = "{ apiVersion }".repeat(1000);
+        let pointer = query.as_ptr();
+        let request = GqlRequest::new_with_op(String::from("apiVersion"), query);
+        assert_eq!(request.query.as_ptr(), pointer);
+
+        let request = GqlRequest::builder()
+            .query(String::from("{ apiVersion }"))
+            .build();
+        assert_eq!(request.query, "{ apiVersion }");
+    }
+
+    #[test]
+    fn typed_variables_test() {
+        #[derive(Serialize)]
+        struct Variables {
+            first: u32,
+            after: Option<String>,
+        }
+        let request = GqlRequest::with_typed_variables(
+            "query books($first: Int, $after: String) { books }",
+            "books",
+            &Variables {
+                first: 10,
+                after: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(request.operation_name.as_deref(), Some("books"));
+        assert_eq!(
+            serde_json::json!(request)["variables"],
+            serde_json::json!({ "first": 10, "after": null })
+        );
+
+        assert!(GqlRequest::with_typed_variables("{ a }", "a", &[1, 2]).is_err());
+    }
+
+    #[test]
+    fn to_json_test() {
+        let request = GqlRequest::new_with_op("books", "query books($b: Int, $a: Int) { books }")
+            .with_variable("b", &2)
+            .and_then(|request| request.with_variable("a", &1))
+            .unwrap();
+        let json = r#"{"operationName":"books","variables":{"a":1,"b":2},"query":"query books($b: Int, $a: Int) { books }"}"#;
+        assert_eq!(request.to_json().unwrap(), json);
+        assert_eq!(request.to_vec().unwrap(), json.as_bytes());
+        assert_eq!(
+            GqlRequest::new("{ a }").to_json().unwrap(),
+            r#"{"query":"{ a }"}"#
+        );
+
+        let pretty = request.to_pretty_json().unwrap();
+        assert!(pretty.starts_with("{\n  \"operationName\": \"books\",\n"));
+    }
+
+    #[test]
+    fn to_curl_test() {
+        let request = GqlRequest::builder()
+            .header("X-Request-Id", "42")
+            .query("query { book(title: \"Rock'n'roll\") { id } }")
+            .build();
+        assert_eq!(
+            request.to_curl("http://localhost/graphql", &[]).unwrap(),
+            r#"curl -X POST 'http://localhost/graphql' -H 'Content-Type: application/json' -H 'X-Request-Id: 42' --data-raw '{"query":"query { book(title: \"Rock'\''n'\''roll\") { id } }"}'"#
+        );
+    }
+
+    #[test]
+    fn query_string_test() {
+        let mut request = GqlRequest::new_with_op("žluť", "{ a }");
+        request.set_extensions(serde_json::json!({ "v": 1 }));
+        assert_eq!(
+            request.to_query_string(),
+            "query=%7B%20a%20%7D&operationName=%C5%BElu%C5%A5&extensions=%7B%22v%22%3A1%7D"
+        );
+        assert_eq!(
+            GqlRequest::new("a-b_c.d~e&f").to_query_string(),
+            "query=a-b_c.d~e%26f"
+        );
+    }
+
+    #[test]
+    fn nullable_variable_test() {
+        let mut request = GqlRequest::new_with_op(
+            "updateBook",
+            "mutation updateBook($id: ID!, $subtitle: String, $pages: Int) { updateBook { id } }",
+        );
+        request.add_variable("id", &1).unwrap();
+        request.add_null_variable("subtitle");
+        request.add_variable("pages", &100).unwrap();
+        request
+            .add_nullable_variable::<u32>("pages", Nullable::Absent)
+            .unwrap();
+        assert_eq!(
+            serde_json::json!(request)["variables"],
+            serde_json::json!({ "id": 1, "subtitle": null })
+        );
+
+        request
+            .add_nullable_variable("pages", Nullable::from(Some(5)))
+            .unwrap();
+        assert_eq!(request.variables["pages"], 5);
+        assert_eq!(request.remove_variable("pages"), Some(serde_json::json!(5)));
+        assert!(!request.variables.contains_key("pages"));
+    }
+
+    #[test]
+    fn upload_test() {
+        let mut request = GqlRequest::new(
+            "mutation($image: Upload!, $gallery: [Upload!]!) { upload(image: $image, gallery: $gallery) }",
+        );
+        request.add_variable("gallery", &[(), ()]).unwrap();
+        let image = Upload::new("cover.png", "image/png", vec![137, 80]);
+        request.add_upload("image", image.clone()).unwrap();
+        request
+            .add_upload("gallery.1", Upload::new("b.jpg", "image/jpeg", vec![]))
+            .unwrap();
+        assert!(request.add_upload("gallery.5", image.clone()).is_err());
+
+        assert_eq!(
+            serde_json::json!(request)["variables"],
+            serde_json::json!({ "gallery": [null, null], "image": null })
+        );
+        assert_eq!(
+            request.multipart_map().unwrap(),
+            serde_json::json!({ "0": ["variables.image"], "1": ["variables.gallery.1"] })
+        );
+        assert_eq!(request.uploads[0].1, image);
+
+        let cover = Upload::new("cover.jpg", "image/jpeg", vec![]);
+        request.add_upload("image", cover.clone()).unwrap();
+        assert_eq!(request.uploads.len(), 2);
+        assert_eq!(request.uploads[0].1, cover);
+
+        let mut request = GqlRequest::new("mutation($file: Upload!) { upload(file: $file) }");
+        request.add_upload("file", image).unwrap();
+        request.set_variables([("config", serde_json::json!({ "a": 1 }))]);
+        assert_eq!(
+            request.multipart_map().unwrap_err().to_string(),
+            "Upload variable `file` is no longer null"
+        );
+    }
+
+    #[test]
+    fn graphiql_url_test() {
+        let request = GqlRequest::new_with_op("books", "query books($a: Int) { books }")
+            .with_variable("a", &1)
+            .unwrap();
+        assert_eq!(
+            request.to_graphiql_url("https://example.com/graphiql?theme=dark"),
+            "https://example.com/graphiql?theme=dark&query=query%20books%28%24a%3A%20Int%29%20%7B%20books%20%7D\
+             &operationName=books&variables=%7B%22a%22%3A1%7D"
+        );
+        assert_eq!(
+            GqlRequest::new("{ a }").to_graphiql_url("http://x/graphiql#frag"),
+            "http://x/graphiql?query=%7B%20a%20%7D#frag"
+        );
+    }
+
+    #[test]
+    fn variable_value_test() {
+        let mut request = GqlRequest::new("query($a: Int, $b: Int) { a }");
+        request.add_variable_value("old", Value::Null);
+
+        let mut variables = std::collections::HashMap::new();
+        variables.insert("a".to_string(), serde_json::json!(1));
+        request.set_variables(variables);
+        request.add_variable_value("b", serde_json::json!(2));
+        assert_eq!(
+            serde_json::json!(request)["variables"],
+            serde_json::json!({ "a": 1, "b": 2 })
+        );
+    }
+
+    #[test]
+    fn chaining_test() {
+        let request = GqlRequest::new("query books($first: Int, $after: String) { books }")
+            .with_operation("books")
+            .with_variable("first", &10)
+            .and_then(|request| request.with_variable("after", &"abc"))
+            .unwrap();
+        assert_eq!(request.operation_name.as_deref(), Some("books"));
+        assert_eq!(request.variables["first"], 10);
+        assert_eq!(request.variables["after"], "abc");
+
+        let request = GqlRequest::new("query($a: Int, $b: Int) { a }")
+            .with_variable("a", &1)
+            .and_then(|request| request.with_variable("b", &2))
+            .unwrap();
+        assert!(request.operation_name.is_none());
+        assert_eq!(request.variables.len(), 2);
+    }
+
+    #[test]
+    fn inferred_operation_test() {
+        let request = GqlRequest::with_inferred_operation(
+            "# books\nquery Books($first: Int) { books(first: $first) { ...F } }\nfragment F on Book { title }",
+        );
+        assert_eq!(request.operation_name.as_deref(), Some("Books"));
+
+        let request = GqlRequest::with_inferred_operation("query A { a } query B { b }");
+        assert!(request.operation_name.is_none());
+        let request = GqlRequest::with_inferred_operation("query($first: Int) { books }");
+        assert!(request.operation_name.is_none());
+    }
+
+    #[test]
+    fn from_file_test() {
+        let query = "mutation createBook($book: createBook!) {\n  createBook(book: $book) {\n    title\n  }\n}\n";
+        let path = std::env::temp_dir().join("gqlrequest_from_file_test.graphql");
+        std::fs::write(&path, query).unwrap();
+
+        let request = GqlRequest::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(request.operation_name.as_deref(), Some("createBook"));
+        assert_eq!(request.query, query);
+
+        let error = GqlRequest::from_file(&path).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Failed to read GraphQL document"));
+
+        let request = GqlRequest::from_reader("{ apiVersion }".as_bytes()).unwrap();
+        assert!(request.operation_name.is_none());
+    }
+
+    #[test]
+    fn builder_test() {
+        let query = "query apiVersion { apiVersion }";
+        let request = GqlRequestBuilder::new().query(query).build();
+        assert!(request.operation_name.is_none());
+        assert_eq!(request.query, query);
+
+        let request = GqlRequestBuilder::new()
+            .query(query)
+            .operation_name("apiVersion")
+            .build();
+        let expected = serde_json::json!({
+            "operationName": "apiVersion",
+            "query": query,
+        });
+        assert_eq!(serde_json::json!(request), expected);
+    }
+
+    #[test]
+    fn builder_meta_test() {
+        let request = GqlRequest::builder()
+            .header("Authorization", "Bearer token")
+            .endpoint("https://example.com/graphql")
+            .query("{ apiVersion }")
+            .timeout(std::time::Duration::from_secs(5))
+            .build();
+        assert_eq!(request.meta.header("authorization"), Some("Bearer token"));
+        assert_eq!(
+            request.meta.endpoint.as_deref(),
+            Some("https://example.com/graphql")
+        );
+        assert_eq!(
+            request.meta.timeout,
+            Some(std::time::Duration::from_secs(5))
+        );
+        assert_eq!(
+            serde_json::json!(request),
+            serde_json::json!({ "query": "{ apiVersion }" })
+        );
+    }
+
+    #[test]
+    fn builder_variables_test() {
+        #[derive(Serialize)]
+        struct TestQuery {
+            pub title: String,
+        }
+        let query = "mutation ($book: createBook!, $draft: Boolean) { createBook(book: $book, draft: $draft) { title }}";
+        let request = GqlRequest::builder()
+            .query(query)
+            .variable(
+                "book",
+                &TestQuery {
+                    title: "Rocket Engineering".to_string(),
+                },
+            )
+            .variables(vec![("draft", true)])
+            .build();
+
+        let expected = serde_json::json!({
+            "variables": {
+                "book": { "title": "Rocket Engineering" },
+                "draft": true,
+            },
+            "query": query,
+        });
+        assert_eq!(serde_json::json!(request), expected);
+    }
+
+    #[test]
+    fn validate_test() {
+        assert!(GqlRequest::new("{ apiVersion }").validate().is_ok());
+        assert_eq!(
+            GqlRequest::new("").validate().unwrap_err(),
+            ValidationError::EmptyQuery
+        );
+
+        let request = GqlRequest::new_with_variable(
+            "mutation ($title: String!) { createBook(title: $title) { title }}",
+            "title",
+            &"Rocket\0Engineering",
+        );
+        let error = request.validate().unwrap_err();
+        assert_eq!(error.to_string(), "Variable `title` contains NUL character");
+    }
+
+    #[test]
+    fn envelope_response_test() {
+        let body = r#"{ "result": { "data": { "apiVersion": "1.0" } }, "meta": { "took": 3 } }"#;
+
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApiVersion {
+            api_version: String,
+        }
+
+        let response: GqlResponse<ApiVersion> =
+            GqlResponse::from_envelope(body, "/result").unwrap();
+        assert_eq!(response.data.unwrap().api_version, "1.0");
+
+        assert!(GqlResponse::<ApiVersion>::from_envelope(body, "/payload").is_err());
+    }
+
+    #[test]
+    fn limits_test() {
+        let request = GqlRequest::new_with_variable(
+            "mutation ($title: String!) { createBook(title: $title) { title }}",
+            "title",
+            &"Rocket Engineering",
+        );
+        assert!(request.check_limits(&RequestLimits::default()).is_ok());
+
+        let limits = RequestLimits {
+            max_variables_size: 16,
+            ..RequestLimits::default()
+        };
+        assert!(matches!(
+            request.check_limits(&limits),
+            Err(ValidationError::VariablesTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn strict_response_test() {
+        let body = r#"{ "data": { "apiVersion": "1.0" }, "extensions": { "cost": 1 } }"#;
+        let response: GqlResponse<Value> = GqlResponse::from_str_strict(body).unwrap();
+        assert_eq!(response.extensions.unwrap()["cost"], 1);
+
+        let body = r#"{ "data": { "apiVersion": "1.0" }, "status": "ok", "proxy": true }"#;
+        let error = GqlResponse::<Value>::from_str_strict(body).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "GraphQL response contains unexpected top-level keys: proxy, status"
+        );
+        assert!(serde_json::from_str::<GqlResponse<Value>>(body).is_ok());
+    }
+
+    #[test]
+    fn into_parts_test() {
+        let body = r#"{ "data": { "apiVersion": "1.0", "count": 3 } }"#;
+        let response: GqlResponse<Value> = serde_json::from_str(body).unwrap();
+        let (version, count, missing) = response
+            .into_parts::<(String, u32, Option<String>)>(["apiVersion", "count", "missing"])
+            .unwrap();
+        assert_eq!(version, "1.0");
+        assert_eq!(count, 3);
+        assert!(missing.is_none());
+
+        let response: GqlResponse<Value> = serde_json::from_str(body).unwrap();
+        let error = response.into_parts::<(u32,)>(["apiVersion"]).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Failed to deserialize data field `apiVersion`"));
+
+        let response: GqlResponse<Value> = serde_json::from_str(body).unwrap();
+        let error = response
+            .into_parts::<(u32, u32)>(["count", "count"])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Data field `count` is requested more than once"
+        );
+    }
+
+    #[test]
+    fn dedup_errors_test() {
+        let body = r#"{ "data": { "books": [null, null, null] }, "errors": [
+            { "message": "Not allowed", "locations": [], "path": ["books", 0, "isbn"], "extensions": { "code": "FORBIDDEN" } },
+            { "message": "Not allowed", "locations": [], "path": ["books", 1, "isbn"], "extensions": { "code": "FORBIDDEN" } },
+            { "message": "Not allowed", "locations": [], "path": ["books", 2, "isbn"], "extensions": { "code": "FORBIDDEN" } },
+            { "message": "Timeout", "locations": [], "path": ["books", 2, "author"], "extensions": { "code": "TIMEOUT" } }
+        ] }"#;
+        let mut response: GqlResponse<Value> = serde_json::from_str(body).unwrap();
+
+        let summary = response.error_summary().unwrap();
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.paths, 4);
+        assert_eq!(
+            summary.to_string(),
+            "4 errors across 4 paths, top codes: FORBIDDEN (3), TIMEOUT (1)"
+        );
+
+        let errors = response.errors.as_ref().unwrap();
+        assert_eq!(errors[0].code(), Some("FORBIDDEN"));
+        assert!(errors[0].is_auth() && !errors[0].is_retryable());
+        assert!(errors[3].is_retryable() && !errors[3].is_auth());
+
+        response.dedup_errors();
+        let errors = response.errors.as_ref().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "Not allowed");
+        assert_eq!(errors[1].message, "Timeout");
+
+        let response: GqlResponse<Value> = serde_json::from_str(r#"{ "data": {} }"#).unwrap();
+        assert!(response.error_summary().is_none());
+    }
+
+    #[test]
+    fn location_test() {
+        let query = "query {\r\n  žluť\n  named\n}";
+        let location = |line, column| Location { line, column };
+        assert_eq!(location(1, 1).to_byte_offset(query), Some(0));
+        assert_eq!(location(1, 8).to_byte_offset(query), Some(7));
+        assert_eq!(location(1, 9).to_byte_offset(query), None);
+        assert_eq!(location(2, 4).to_byte_offset(query), Some(13));
+        assert_eq!(location(3, 3).to_byte_offset(query), Some(20));
+        assert_eq!(&query[20..25], "named");
+        assert_eq!(location(5, 1).to_byte_offset(query), None);
+        assert_eq!(location(0, 1).to_byte_offset(query), None);
+        assert_eq!(location(1, 0).to_byte_offset(query), None);
+
+        let query = "{\r  a\r\n  b\n}";
+        assert_eq!(location(2, 3).to_byte_offset(query), Some(4));
+        assert_eq!(location(3, 3).to_byte_offset(query), Some(9));
+        assert_eq!(location(4, 1).to_byte_offset(query), Some(11));
+
+        assert!(serde_json::from_str::<Location>(r#"{ "line": 0, "column": 1 }"#).is_err());
+        assert!(serde_json::from_str::<Location>(r#"{ "line": -1, "column": 1 }"#).is_err());
+    }
+
     /// Error taken from: https://lucasconstantino.github.io/graphiql-online/
     #[test]
     fn error_response_ext_test() {
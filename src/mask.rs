@@ -0,0 +1,123 @@
+use serde_json::value::Value;
+
+/// What happens with values matched by [`GqlResponse::mask`](crate::GqlResponse::mask)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskMode {
+    /// Replaces value with `null`, keeps shape of response
+    Nullify,
+    /// Removes field from object or element from list
+    Remove,
+}
+
+/// Masks all values matching `path` pattern
+///
+/// Segments are separated by `.`, `*` matches any field or list index and
+/// `**` matches any number of segments.
+pub(crate) fn mask(value: &mut Value, path: &str, mode: MaskMode) {
+    let segments: Vec<&str> = path.split('.').collect();
+    mask_segments(value, &segments, mode);
+}
+
+fn mask_segments(value: &mut Value, segments: &[&str], mode: MaskMode) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if *segment == "**" {
+        // `**` matching zero segments
+        if rest.is_empty() {
+            apply(value, |_| true, mode);
+        } else {
+            mask_segments(value, rest, mode);
+        }
+        // `**` matching one or more segments
+        for_each_child(value, |_, child| mask_segments(child, segments, mode));
+        return;
+    }
+
+    let matches = |key: &str| *segment == "*" || *segment == key;
+    if rest.is_empty() {
+        apply(value, matches, mode);
+    } else {
+        for_each_child(value, |key, child| {
+            if matches(key) {
+                mask_segments(child, rest, mode);
+            }
+        });
+    }
+}
+
+/// Masks children of `value` with matching key or index
+fn apply(value: &mut Value, matches: impl Fn(&str) -> bool, mode: MaskMode) {
+    match (value, mode) {
+        (Value::Object(map), MaskMode::Remove) => map.retain(|key, _| !matches(key)),
+        (Value::Array(items), MaskMode::Remove) => {
+            let mut index = 0;
+            items.retain(|_| {
+                index += 1;
+                !matches(&(index - 1).to_string())
+            });
+        }
+        (value, MaskMode::Nullify) => for_each_child(value, |key, child| {
+            if matches(key) {
+                *child = Value::Null;
+            }
+        }),
+        _ => {}
+    }
+}
+
+fn for_each_child(value: &mut Value, mut f: impl FnMut(&str, &mut Value)) {
+    match value {
+        Value::Object(map) => map.iter_mut().for_each(|(key, child)| f(key, child)),
+        Value::Array(items) => items
+            .iter_mut()
+            .enumerate()
+            .for_each(|(index, child)| f(&index.to_string(), child)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users() -> Value {
+        serde_json::json!({
+            "users": [
+                { "name": "Filip", "email": "filip@example.com", "address": { "email": "a@example.com" } },
+                { "name": "Ann", "email": "ann@example.com" }
+            ]
+        })
+    }
+
+    #[test]
+    fn nullify_test() {
+        let mut value = users();
+        mask(&mut value, "users.*.email", MaskMode::Nullify);
+        assert_eq!(value["users"][0]["email"], Value::Null);
+        assert_eq!(value["users"][1]["email"], Value::Null);
+        assert_eq!(value["users"][0]["address"]["email"], "a@example.com");
+        assert_eq!(value["users"][0]["name"], "Filip");
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut value = users();
+        mask(&mut value, "**.email", MaskMode::Remove);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "users": [
+                    { "name": "Filip", "address": {} },
+                    { "name": "Ann" }
+                ]
+            })
+        );
+
+        let mut value = users();
+        mask(&mut value, "users.0", MaskMode::Remove);
+        assert_eq!(value["users"].as_array().unwrap().len(), 1);
+        assert_eq!(value["users"][0]["name"], "Ann");
+    }
+}
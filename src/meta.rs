@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+/// Transport hints carried with [`GqlRequest`](crate::GqlRequest)
+///
+/// Never serialized into request body, HTTP integrations read it to set
+/// headers, pick endpoint and apply timeout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestMeta {
+    /// Extra HTTP headers in insertion order, e.g. `Authorization`
+    pub headers: Vec<(String, String)>,
+    /// Endpoint overriding client default
+    pub endpoint: Option<String>,
+    /// Timeout overriding client default
+    pub timeout: Option<Duration>,
+}
+
+impl RequestMeta {
+    /// Value of first header with `name`, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_test() {
+        let meta = RequestMeta {
+            headers: vec![("Authorization".to_string(), "Bearer token".to_string())],
+            ..RequestMeta::default()
+        };
+        assert_eq!(meta.header("authorization"), Some("Bearer token"));
+        assert_eq!(meta.header("x-request-id"), None);
+    }
+}
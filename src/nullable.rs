@@ -0,0 +1,62 @@
+use serde::{Serialize, Serializer};
+
+/// Variable value which distinguishes explicit `null` from omitted variable
+///
+/// Useful for partial-update mutations, where `null` clears field and omitted
+/// variable keeps it. In typed variables structs skip absent fields with
+/// `#[serde(skip_serializing_if = "Nullable::is_absent")]`, in
+/// [`GqlRequest`](crate::GqlRequest) use
+/// [`add_nullable_variable`](crate::GqlRequest::add_nullable_variable).
+///
+/// ```
+/// use gqlrequest::Nullable;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct UpdateBook {
+///     id: u32,
+///     #[serde(skip_serializing_if = "Nullable::is_absent")]
+///     subtitle: Nullable<String>,
+///     #[serde(skip_serializing_if = "Nullable::is_absent")]
+///     pages: Nullable<u32>,
+/// }
+///
+/// let update = UpdateBook { id: 1, subtitle: Nullable::Null, pages: Nullable::Absent };
+/// assert_eq!(serde_json::json!(update), serde_json::json!({ "id": 1, "subtitle": null }));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Nullable<T> {
+    /// Variable is not sent
+    #[default]
+    Absent,
+    /// Variable is sent as `null`
+    Null,
+    /// Variable is sent with value
+    Value(T),
+}
+
+impl<T> Nullable<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Nullable::Absent)
+    }
+}
+
+/// `None` is explicit `null`
+impl<T> From<Option<T>> for Nullable<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Nullable::Value(value),
+            None => Nullable::Null,
+        }
+    }
+}
+
+/// `Absent` serializes as `null` too, skip it with `skip_serializing_if`
+impl<T: Serialize> Serialize for Nullable<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Nullable::Value(value) => value.serialize(serializer),
+            Nullable::Absent | Nullable::Null => serializer.serialize_none(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use eyre::Result;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Tuple of types deserialized from top-level `data` fields
+///
+/// Implemented for tuples of up to 6 elements, see
+/// [`GqlResponse::into_parts`](crate::GqlResponse::into_parts).
+pub trait FromDataParts: Sized {
+    /// Array with one field name per tuple element
+    type Fields<'a>;
+
+    fn from_data_parts(data: &mut Map<String, Value>, fields: Self::Fields<'_>) -> Result<Self>;
+}
+
+fn take_part<T: DeserializeOwned>(data: &mut Map<String, Value>, field: &str) -> Result<T> {
+    let value = data.remove(field).unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|error| eyre::eyre!("Failed to deserialize data field `{}`: {}", field, error))
+}
+
+/// Rejects field requested twice, second read would see it already taken
+fn check_unique(fields: &[&str]) -> Result<()> {
+    match fields
+        .iter()
+        .enumerate()
+        .find(|(index, field)| fields[..*index].contains(field))
+    {
+        Some((_, field)) => Err(eyre::eyre!(
+            "Data field `{}` is requested more than once",
+            field
+        )),
+        None => Ok(()),
+    }
+}
+
+macro_rules! impl_from_data_parts {
+    ($n:literal; $($part:ident $index:tt),+) => {
+        impl<$($part: DeserializeOwned),+> FromDataParts for ($($part,)+) {
+            type Fields<'a> = [&'a str; $n];
+
+            fn from_data_parts(data: &mut Map<String, Value>, fields: [&str; $n]) -> Result<Self> {
+                check_unique(&fields)?;
+                Ok(($(take_part::<$part>(data, fields[$index])?,)+))
+            }
+        }
+    };
+}
+
+impl_from_data_parts!(1; A 0);
+impl_from_data_parts!(2; A 0, B 1);
+impl_from_data_parts!(3; A 0, B 1, C 2);
+impl_from_data_parts!(4; A 0, B 1, C 2, D 3);
+impl_from_data_parts!(5; A 0, B 1, C 2, D 3, E 4);
+impl_from_data_parts!(6; A 0, B 1, C 2, D 3, E 4, F 5);
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Deserializer};
+
+/// Error returned as data inside mutation payload, e.g. `userErrors { field message }`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UserError {
+    pub message: String,
+    /// Path to input field, single string is accepted as one element path
+    #[serde(default, deserialize_with = "field_path")]
+    pub field: Option<Vec<String>>,
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// Mutation payload which returns errors as data
+///
+/// User errors are read from `userErrors` (or `errors`) and remaining fields
+/// are deserialized into `T`.
+///
+/// ```
+/// use gqlrequest::MutationPayload;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct CreateBook {
+///     book: Option<Book>,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Book {
+///     title: String,
+/// }
+///
+/// let body = r#"{ "book": null, "userErrors": [ { "field": ["title"], "message": "Title is taken" } ] }"#;
+/// let payload: MutationPayload<CreateBook> = serde_json::from_str(body).unwrap();
+/// let errors = payload.into_result().unwrap_err();
+/// assert_eq!(errors[0].message, "Title is taken");
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MutationPayload<T> {
+    #[serde(default, alias = "errors")]
+    pub user_errors: Vec<UserError>,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+impl<T> MutationPayload<T> {
+    /// Returns data when there are no user errors
+    pub fn into_result(self) -> Result<T, Vec<UserError>> {
+        if self.user_errors.is_empty() {
+            Ok(self.data)
+        } else {
+            Err(self.user_errors)
+        }
+    }
+}
+
+fn field_path<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Field {
+        One(String),
+        Path(Vec<String>),
+    }
+
+    Ok(match Option::<Field>::deserialize(deserializer)? {
+        Some(Field::One(field)) => Some(vec![field]),
+        Some(Field::Path(path)) => Some(path),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct UpdateUser {
+        user: Option<User>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct User {
+        name: String,
+    }
+
+    #[test]
+    fn success_test() {
+        let body = r#"{ "user": { "name": "Filip" }, "userErrors": [] }"#;
+        let payload: MutationPayload<UpdateUser> = serde_json::from_str(body).unwrap();
+        let data = payload.into_result().unwrap();
+        assert_eq!(data.user.unwrap().name, "Filip");
+
+        let body = r#"{ "user": { "name": "Filip" } }"#;
+        let payload: MutationPayload<UpdateUser> = serde_json::from_str(body).unwrap();
+        assert!(payload.into_result().is_ok());
+    }
+
+    #[test]
+    fn user_errors_test() {
+        let body = r#"{ "user": null, "errors": [ { "field": "name", "message": "Name is too long", "code": "TOO_LONG" } ] }"#;
+        let payload: MutationPayload<UpdateUser> = serde_json::from_str(body).unwrap();
+        let errors = payload.into_result().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![UserError {
+                message: "Name is too long".to_string(),
+                field: Some(vec!["name".to_string()]),
+                code: Some("TOO_LONG".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Commonly used types and traits
+//!
+//! ```
+//! use gqlrequest::prelude::*;
+//!
+//! let request = GqlRequest::builder().query("{ apiVersion }").build();
+//! assert!(request.validate().is_ok());
+//! ```
+
+pub use crate::{gql_enum, include_gql};
+pub use crate::{
+    DataState, ErrorMsg, ErrorSummary, FromDataParts, GqlId, GqlRequest, GqlRequestBuilder,
+    GqlResponse, GqlTemplate, Location, MaskMode, MutationPayload, Nullable, OperationKind,
+    RequestLimits, RequestMeta, SubscriptionItem, Upload, UserError, ValidationError,
+};
@@ -0,0 +1,65 @@
+use crate::GqlResponse;
+use serde_json::value::Value;
+
+/// Item of subscription stream
+///
+/// Besides data it exposes connection lifecycle, so consumers can react to
+/// acknowledgement, keep-alive messages, completion and connection errors.
+#[derive(Debug)]
+pub enum SubscriptionItem<T> {
+    /// Subscription event
+    Data(GqlResponse<T>),
+    /// Server accepted connection
+    ConnectionAck,
+    /// Keep-alive (ping/pong) message
+    KeepAlive,
+    /// Server completed subscription
+    Complete,
+    /// Server rejected or closed connection, with optional payload
+    ConnectionError(Option<Value>),
+}
+
+impl<T> SubscriptionItem<T> {
+    /// Returns event data, lifecycle items are `None`
+    pub fn into_data(self) -> Option<GqlResponse<T>> {
+        match self {
+            SubscriptionItem::Data(response) => Some(response),
+            _ => None,
+        }
+    }
+
+    /// True for `Complete` and `ConnectionError`, no more items follow
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            SubscriptionItem::Complete | SubscriptionItem::ConnectionError(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn item_test() {
+        let response: GqlResponse<Value> =
+            serde_json::from_str(r#"{ "data": { "bookAdded": { "title": "Rocket" } } }"#).unwrap();
+        let item = SubscriptionItem::Data(response);
+        assert!(!item.is_terminal());
+        let data = item.into_data().unwrap().data.unwrap();
+        assert_eq!(data["bookAdded"]["title"], "Rocket");
+
+        let item = SubscriptionItem::Data(GqlResponse::new(
+            crate::DataState::Present(serde_json::json!({ "bookAdded": null })),
+            None,
+            None,
+        ));
+        assert!(item.into_data().unwrap().data.is_some());
+
+        let item: SubscriptionItem<Value> = SubscriptionItem::ConnectionError(None);
+        assert!(item.is_terminal());
+        assert!(item.into_data().is_none());
+        assert!(!SubscriptionItem::<Value>::KeepAlive.is_terminal());
+    }
+}
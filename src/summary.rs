@@ -0,0 +1,71 @@
+use crate::ErrorMsg;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+/// Number of codes shown by `Display` of [`ErrorSummary`]
+const TOP_CODES: usize = 3;
+
+/// Aggregated view of response errors
+///
+/// Displays as `N errors across M paths, top codes: CODE (count), ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorSummary {
+    /// Number of errors
+    pub total: usize,
+    /// Number of distinct error paths
+    pub paths: usize,
+    /// Error codes from `extensions.code` with counts, most frequent first
+    pub codes: Vec<(String, usize)>,
+}
+
+impl ErrorSummary {
+    pub(crate) fn new(errors: &[ErrorMsg]) -> Self {
+        let paths: BTreeSet<String> = errors
+            .iter()
+            .filter_map(|error| error.path.as_ref())
+            .map(|path| serde_json::json!(path).to_string())
+            .collect();
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for code in errors.iter().filter_map(ErrorMsg::code) {
+            *counts.entry(code).or_default() += 1;
+        }
+        let mut codes: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(code, count)| (code.to_string(), count))
+            .collect();
+        codes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        ErrorSummary {
+            total: errors.len(),
+            paths: paths.len(),
+            codes,
+        }
+    }
+}
+
+impl fmt::Display for ErrorSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} errors across {} paths", self.total, self.paths)?;
+        if self.codes.is_empty() {
+            return Ok(());
+        }
+        write!(f, ", top codes: ")?;
+        for (index, (code, count)) in self.codes.iter().take(TOP_CODES).enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} ({})", code, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes errors with the same message and extensions, first one is kept
+pub(crate) fn dedup(errors: &mut Vec<ErrorMsg>) {
+    let mut seen = HashSet::new();
+    errors.retain(|error| {
+        let extensions = error.extensions.as_ref().map(ToString::to_string);
+        seen.insert((error.message.clone(), extensions))
+    });
+}
@@ -0,0 +1,106 @@
+use crate::GqlRequest;
+use serde::Serialize;
+
+/// Reusable request with default variables
+///
+/// Query, operation name, extensions and default variables are prepared once,
+/// every stamped request only overrides variables which change.
+///
+/// ```
+/// use gqlrequest::{GqlRequest, GqlTemplate};
+///
+/// let template = GqlTemplate::from(
+///     GqlRequest::builder()
+///         .operation_name("createBook")
+///         .query("mutation createBook($title: String!, $draft: Boolean) { createBook(title: $title, draft: $draft) { id } }")
+///         .variable("draft", &true)
+///         .build(),
+/// );
+/// let request = template.request_with([("title", "Rocket Engineering")]);
+/// assert_eq!(request.variables["title"], "Rocket Engineering");
+/// assert_eq!(request.variables["draft"], true);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GqlTemplate {
+    request: GqlRequest,
+}
+
+impl GqlTemplate {
+    /// Request with default variables only
+    pub fn request(&self) -> GqlRequest {
+        self.request.clone()
+    }
+
+    /// Request with `overrides` added to default variables
+    ///
+    /// # Panics
+    ///
+    /// Panics when any override cannot be serialized to JSON, e.g. map with
+    /// non-string keys. Use [`GqlRequest::add_variable`] on [`GqlTemplate::request`]
+    /// to get error instead.
+    pub fn request_with<I, K, T>(&self, overrides: I) -> GqlRequest
+    where
+        I: IntoIterator<Item = (K, T)>,
+        K: Into<String>,
+        T: Serialize,
+    {
+        let mut request = self.request();
+        request.variables.extend(
+            overrides
+                .into_iter()
+                .map(|(name, object)| (name.into(), serde_json::json!(object))),
+        );
+        request
+    }
+
+    /// Request with one variable added to default variables
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`GqlTemplate::request_with`].
+    pub fn request_with_variable<T: Serialize>(
+        &self,
+        name: impl Into<String>,
+        object: &T,
+    ) -> GqlRequest {
+        self.request_with(std::iter::once((name, object)))
+    }
+
+    /// Request used as template
+    pub fn as_request(&self) -> &GqlRequest {
+        &self.request
+    }
+}
+
+impl From<GqlRequest> for GqlTemplate {
+    fn from(request: GqlRequest) -> Self {
+        GqlTemplate { request }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_test() {
+        let template = GqlTemplate::from(
+            GqlRequest::new_with_op(
+                "setStock",
+                "mutation setStock($id: ID!, $count: Int!) { setStock(id: $id, count: $count) }",
+            )
+            .with_variable("id", &"book-1")
+            .unwrap()
+            .with_variable("count", &0)
+            .unwrap(),
+        );
+
+        let request = template.request_with_variable("count", &5);
+        assert_eq!(request.operation_name.as_deref(), Some("setStock"));
+        assert_eq!(request.variables["id"], "book-1");
+        assert_eq!(request.variables["count"], 5);
+
+        assert_eq!(template.request(), *template.as_request());
+        assert_eq!(template.as_request().variables["count"], 0);
+    }
+}
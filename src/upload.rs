@@ -0,0 +1,111 @@
+use eyre::Result;
+use serde_json::value::Value;
+use std::collections::BTreeMap;
+
+/// File sent with GraphQL multipart request
+///
+/// File content is sent in separate multipart part and its variable is `null`.
+/// Add it with [`GqlRequest::add_upload`](crate::GqlRequest::add_upload), which
+/// records its position for the multipart `map` field. `Upload` is
+/// intentionally not `Serialize`, so it cannot end up in variables without
+/// being recorded. Variables changed later can drop `null` placeholder,
+/// [`GqlRequest::multipart_map`](crate::GqlRequest::multipart_map) then fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upload {
+    pub file_name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+impl Upload {
+    pub fn new(
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        content: Vec<u8>,
+    ) -> Self {
+        Upload {
+            file_name: file_name.into(),
+            content_type: content_type.into(),
+            content,
+        }
+    }
+}
+
+/// Sets `null` at dotted `path` in variables, e.g. `file` or `files.1`
+///
+/// Top-level variable is created, nested path must point to element of
+/// existing object or list. Variables are unchanged on error.
+pub(crate) fn place(variables: &mut BTreeMap<String, Value>, path: &str) -> Result<()> {
+    let mut segments = path.split('.');
+    let name = segments.next().unwrap_or_default();
+    if name.is_empty() {
+        return Err(eyre::eyre!("Upload path is empty"));
+    }
+    if !path.contains('.') {
+        variables.insert(name.to_string(), Value::Null);
+        return Ok(());
+    }
+    let mut value = variables
+        .get_mut(name)
+        .ok_or_else(|| eyre::eyre!("Upload path `{}` does not exist", path))?;
+    for segment in segments {
+        let child = match value {
+            Value::Object(map) => map.get_mut(segment),
+            Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(move |index| items.get_mut(index)),
+            _ => None,
+        };
+        value = child.ok_or_else(|| eyre::eyre!("Upload path `{}` does not exist", path))?;
+    }
+    *value = Value::Null;
+    Ok(())
+}
+
+/// Whether value at dotted `path` in variables is still `null` placeholder
+pub(crate) fn is_placeholder(variables: &BTreeMap<String, Value>, path: &str) -> bool {
+    let mut segments = path.split('.');
+    let mut value = segments.next().and_then(|name| variables.get(name));
+    for segment in segments {
+        value = match value {
+            Some(Value::Object(map)) => map.get(segment),
+            Some(Value::Array(items)) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get(index)),
+            _ => None,
+        };
+    }
+    value == Some(&Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_test() {
+        let mut variables = BTreeMap::new();
+        variables.insert("files".to_string(), serde_json::json!(["a", "b"]));
+        place(&mut variables, "file").unwrap();
+        place(&mut variables, "files.1").unwrap();
+        assert_eq!(
+            serde_json::json!(variables),
+            serde_json::json!({ "file": null, "files": ["a", null] })
+        );
+
+        let before = variables.clone();
+        assert!(place(&mut variables, "files.2").is_err());
+        assert!(place(&mut variables, "missing.0").is_err());
+        assert!(place(&mut variables, "file.0").is_err());
+        assert!(place(&mut variables, "").is_err());
+        assert_eq!(variables, before);
+
+        assert!(is_placeholder(&variables, "file"));
+        assert!(is_placeholder(&variables, "files.1"));
+        assert!(!is_placeholder(&variables, "files.0"));
+        assert!(!is_placeholder(&variables, "files.2"));
+        assert!(!is_placeholder(&variables, "missing"));
+    }
+}
@@ -0,0 +1,538 @@
+use crate::document;
+use crate::lexer::{self, TokenKind};
+use serde_json::value::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Error returned by [`GqlRequest::validate`](crate::GqlRequest::validate) and other checks
+///
+/// New variants may be added, match with wildcard arm or use
+/// [`ValidationError::code`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// Query is empty or contains only whitespace
+    EmptyQuery,
+    /// Query contains control character which is not allowed by GraphQL
+    ControlCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    /// Variable contains NUL character, `path` points to value e.g. `book.title`
+    NulCharacter { path: String },
+    /// Serialized variables are larger than allowed `limit` in bytes
+    ///
+    /// `name` is the largest variable and `sizes` contains size of every
+    /// variable, largest first.
+    VariablesTooLarge {
+        name: String,
+        size: usize,
+        limit: usize,
+        sizes: Vec<(String, usize)>,
+    },
+    /// Query is longer than allowed `limit` in bytes
+    QueryTooLong { length: usize, limit: usize },
+    /// Query has more tokens than allowed `limit`
+    TooManyTokens { limit: usize },
+    /// Query nesting is deeper than allowed `limit`
+    TooDeep {
+        line: usize,
+        column: usize,
+        limit: usize,
+    },
+    /// Query is not valid GraphQL
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Operation to execute is not in query, `name` is `None` when query has
+    /// several operations and request has no operation name
+    OperationNotFound { name: Option<String> },
+    /// Variable of non-null type without default value is missing or `null`
+    MissingVariable { name: String },
+    /// Variable is not declared by operation
+    UnknownVariable { name: String },
+}
+
+impl ValidationError {
+    /// Stable `SCREAMING_SNAKE_CASE` code, codes of existing variants never change
+    ///
+    /// | Variant | Code |
+    /// |---|---|
+    /// | `EmptyQuery` | `EMPTY_QUERY` |
+    /// | `ControlCharacter` | `CONTROL_CHARACTER` |
+    /// | `NulCharacter` | `NUL_CHARACTER` |
+    /// | `VariablesTooLarge` | `VARIABLES_TOO_LARGE` |
+    /// | `QueryTooLong` | `QUERY_TOO_LONG` |
+    /// | `TooManyTokens` | `TOO_MANY_TOKENS` |
+    /// | `TooDeep` | `TOO_DEEP` |
+    /// | `Syntax` | `SYNTAX` |
+    /// | `OperationNotFound` | `OPERATION_NOT_FOUND` |
+    /// | `MissingVariable` | `MISSING_VARIABLE` |
+    /// | `UnknownVariable` | `UNKNOWN_VARIABLE` |
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::EmptyQuery => "EMPTY_QUERY",
+            ValidationError::ControlCharacter { .. } => "CONTROL_CHARACTER",
+            ValidationError::NulCharacter { .. } => "NUL_CHARACTER",
+            ValidationError::VariablesTooLarge { .. } => "VARIABLES_TOO_LARGE",
+            ValidationError::QueryTooLong { .. } => "QUERY_TOO_LONG",
+            ValidationError::TooManyTokens { .. } => "TOO_MANY_TOKENS",
+            ValidationError::TooDeep { .. } => "TOO_DEEP",
+            ValidationError::Syntax { .. } => "SYNTAX",
+            ValidationError::OperationNotFound { .. } => "OPERATION_NOT_FOUND",
+            ValidationError::MissingVariable { .. } => "MISSING_VARIABLE",
+            ValidationError::UnknownVariable { .. } => "UNKNOWN_VARIABLE",
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyQuery => write!(f, "Query is empty"),
+            ValidationError::ControlCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "Query contains control character {:?} at line {} column {}",
+                character, line, column
+            ),
+            ValidationError::NulCharacter { path } => {
+                write!(f, "Variable `{}` contains NUL character", path)
+            }
+            ValidationError::VariablesTooLarge {
+                name,
+                size,
+                limit,
+                sizes,
+            } => {
+                write!(
+                    f,
+                    "Variables have {} bytes which is over limit of {} bytes, largest is `{}` (",
+                    size, limit, name
+                )?;
+                for (index, (name, size)) in sizes.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {} bytes", name, size)?;
+                }
+                write!(f, ")")
+            }
+            ValidationError::QueryTooLong { length, limit } => write!(
+                f,
+                "Query has {} bytes which is over limit of {} bytes",
+                length, limit
+            ),
+            ValidationError::TooManyTokens { limit } => {
+                write!(f, "Query has more than {} tokens", limit)
+            }
+            ValidationError::TooDeep {
+                line,
+                column,
+                limit,
+            } => write!(
+                f,
+                "Query nesting exceeds depth {} at line {} column {}",
+                limit, line, column
+            ),
+            ValidationError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "{} at line {} column {}", message, line, column),
+            ValidationError::OperationNotFound { name: Some(name) } => {
+                write!(f, "Query does not contain operation `{}`", name)
+            }
+            ValidationError::OperationNotFound { name: None } => write!(
+                f,
+                "Query does not contain exactly one operation and operation name is missing"
+            ),
+            ValidationError::MissingVariable { name } => {
+                write!(f, "Required variable `${}` is not provided", name)
+            }
+            ValidationError::UnknownVariable { name } => {
+                write!(f, "Variable `${}` is not declared by operation", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Limits for requests received from untrusted clients
+///
+/// Used by [`GqlRequest::check_limits`](crate::GqlRequest::check_limits).
+/// Tokens and depth are counted while lexing and checking stops at first
+/// exceeded limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Maximum query length in bytes
+    pub max_query_length: usize,
+    /// Maximum number of tokens in query, comments are not counted
+    pub max_tokens: usize,
+    /// Maximum nesting of selection sets, `{ a { b } }` has depth 2
+    ///
+    /// Arguments, lists and input objects are not counted.
+    pub max_depth: usize,
+    /// Maximum size of serialized variables in bytes
+    pub max_variables_size: usize,
+}
+
+impl Default for RequestLimits {
+    /// 64 KiB query, 10 000 tokens, depth 32 and 1 MiB of variables
+    fn default() -> Self {
+        RequestLimits {
+            max_query_length: 64 * 1024,
+            max_tokens: 10_000,
+            max_depth: 32,
+            max_variables_size: 1024 * 1024,
+        }
+    }
+}
+
+/// Checks query against limits, length is checked before query is tokenized
+pub(crate) fn check_query_limits(
+    query: &str,
+    limits: &RequestLimits,
+) -> Result<(), ValidationError> {
+    if query.len() > limits.max_query_length {
+        return Err(ValidationError::QueryTooLong {
+            length: query.len(),
+            limit: limits.max_query_length,
+        });
+    }
+    // Tokens are checked while lexing, so limits bound the work on untrusted input
+    let mut depth = 0usize;
+    let mut parens = 0usize;
+    let mut count = 0usize;
+    for token in lexer::tokens(query) {
+        let token = token.map_err(|error| syntax_error(query, error))?;
+        if token.kind == TokenKind::Comment {
+            continue;
+        }
+        count += 1;
+        if count > limits.max_tokens {
+            return Err(ValidationError::TooManyTokens {
+                limit: limits.max_tokens,
+            });
+        }
+        if token.kind != TokenKind::Punctuator {
+            continue;
+        }
+        match token.text {
+            "(" => parens += 1,
+            ")" => parens = parens.saturating_sub(1),
+            // Braces inside parentheses are input object values
+            "{" if parens == 0 => {
+                depth += 1;
+                if depth > limits.max_depth {
+                    let (line, column) = lexer::line_column(query, token.offset);
+                    return Err(ValidationError::TooDeep {
+                        line,
+                        column,
+                        limit: limits.max_depth,
+                    });
+                }
+            }
+            "}" if parens == 0 => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Checks that query is not empty and contains only GraphQL source characters
+///
+/// Allowed control characters are tab, line feed and carriage return.
+pub(crate) fn check_query(query: &str) -> Result<(), ValidationError> {
+    if query.trim().is_empty() {
+        return Err(ValidationError::EmptyQuery);
+    }
+    for (line, (_, text)) in lexer::lines(query).enumerate() {
+        for (column, character) in text.chars().enumerate() {
+            if character < ' ' && character != '\t' {
+                return Err(ValidationError::ControlCharacter {
+                    line: line + 1,
+                    column: column + 1,
+                    character,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that neither keys nor strings inside variable contain NUL character
+///
+/// Rust strings are always valid UTF-8 so there is no need to check for lone
+/// surrogates.
+pub(crate) fn check_variable(path: &str, value: &Value) -> Result<(), ValidationError> {
+    let nul = || ValidationError::NulCharacter {
+        path: path.to_string(),
+    };
+    if path.contains('\0') {
+        return Err(nul());
+    }
+    match value {
+        Value::String(text) if text.contains('\0') => Err(nul()),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, item)| check_variable(&format!("{}[{}]", path, index), item)),
+        Value::Object(map) => map
+            .iter()
+            .try_for_each(|(key, item)| check_variable(&format!("{}.{}", path, key), item)),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that serialized variables are not larger than `limit` bytes
+///
+/// Request without variables always passes.
+pub(crate) fn check_variables_size(
+    variables: &BTreeMap<String, Value>,
+    limit: usize,
+) -> Result<(), ValidationError> {
+    let size = serde_json::json!(variables).to_string().len();
+    if variables.is_empty() || size <= limit {
+        return Ok(());
+    }
+    let mut sizes: Vec<(String, usize)> = variables
+        .iter()
+        .map(|(name, value)| (name.clone(), value.to_string().len()))
+        .collect();
+    sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    Err(ValidationError::VariablesTooLarge {
+        name: sizes
+            .first()
+            .map(|(name, _)| name.clone())
+            .unwrap_or_default(),
+        size,
+        limit,
+        sizes,
+    })
+}
+
+/// Checks `variables` against variables declared by selected operation
+pub(crate) fn check_declared_variables(
+    query: &str,
+    operation_name: Option<&str>,
+    variables: &BTreeMap<String, Value>,
+) -> Result<(), ValidationError> {
+    let tokens = tokenize(query)?;
+    let operations = document::operations(&tokens);
+    let operation = document::select(&operations, operation_name).ok_or_else(|| {
+        ValidationError::OperationNotFound {
+            name: operation_name.map(str::to_string),
+        }
+    })?;
+
+    for definition in &operation.variables {
+        let provided = variables
+            .get(definition.name)
+            .is_some_and(|value| !value.is_null());
+        if definition.required && !provided {
+            return Err(ValidationError::MissingVariable {
+                name: definition.name.to_string(),
+            });
+        }
+    }
+    match variables.keys().find(|name| {
+        !operation
+            .variables
+            .iter()
+            .any(|definition| definition.name == name.as_str())
+    }) {
+        Some(name) => Err(ValidationError::UnknownVariable { name: name.clone() }),
+        None => Ok(()),
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<lexer::Token<'_>>, ValidationError> {
+    lexer::tokenize(query).map_err(|error| syntax_error(query, error))
+}
+
+fn syntax_error(query: &str, error: lexer::LexError) -> ValidationError {
+    let (line, column) = lexer::line_column(query, error.offset);
+    ValidationError::Syntax {
+        line,
+        column,
+        message: error.message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_test() {
+        assert_eq!(check_query(""), Err(ValidationError::EmptyQuery));
+        assert_eq!(check_query(" \n\t"), Err(ValidationError::EmptyQuery));
+        assert!(check_query("{\r\n\tapiVersion\n}").is_ok());
+        assert_eq!(
+            check_query("{\r  api\0Version\r}").unwrap_err(),
+            ValidationError::ControlCharacter {
+                line: 2,
+                column: 6,
+                character: '\0'
+            }
+        );
+        assert_eq!(
+            check_query("{\n  api\0Version\n}"),
+            Err(ValidationError::ControlCharacter {
+                line: 2,
+                column: 6,
+                character: '\0'
+            })
+        );
+    }
+
+    #[test]
+    fn code_test() {
+        assert_eq!(ValidationError::EmptyQuery.code(), "EMPTY_QUERY");
+        let error = check_query("{\n  api\0Version\n}").unwrap_err();
+        assert_eq!(error.code(), "CONTROL_CHARACTER");
+    }
+
+    #[test]
+    fn declared_variables_test() {
+        let query = "query books($first: Int!, $after: String, $sort: Sort! = TITLE) { books }\n\
+                     query authors { authors }";
+        let variables = |pairs: &[(&str, Value)]| -> BTreeMap<String, Value> {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect()
+        };
+        let check = |name, pairs: &[(&str, Value)]| {
+            check_declared_variables(query, name, &variables(pairs))
+        };
+
+        assert!(check(Some("books"), &[("first", Value::from(10))]).is_ok());
+        assert_eq!(
+            check(Some("books"), &[("first", Value::Null)]),
+            Err(ValidationError::MissingVariable {
+                name: "first".to_string()
+            })
+        );
+        assert_eq!(
+            check(
+                Some("books"),
+                &[("first", Value::from(1)), ("last", Value::from(1))]
+            ),
+            Err(ValidationError::UnknownVariable {
+                name: "last".to_string()
+            })
+        );
+        assert_eq!(
+            check(Some("authors"), &[("first", Value::from(1))])
+                .unwrap_err()
+                .to_string(),
+            "Variable `$first` is not declared by operation"
+        );
+        assert_eq!(
+            check(None, &[]),
+            Err(ValidationError::OperationNotFound { name: None })
+        );
+        assert_eq!(
+            check(Some("users"), &[]).unwrap_err().code(),
+            "OPERATION_NOT_FOUND"
+        );
+    }
+
+    #[test]
+    fn variable_test() {
+        let value = serde_json::json!({ "title": "Rocket", "tags": ["a", "b\0"] });
+        assert_eq!(
+            check_variable("book", &value),
+            Err(ValidationError::NulCharacter {
+                path: "book.tags[1]".to_string()
+            })
+        );
+        assert!(check_variable("book", &serde_json::json!({ "title": "Rocket\n" })).is_ok());
+    }
+
+    #[test]
+    fn query_limits_test() {
+        let limits = RequestLimits {
+            max_query_length: 64,
+            max_tokens: 8,
+            max_depth: 2,
+            max_variables_size: 0,
+        };
+        assert!(check_query_limits("{ a { b } }", &limits).is_ok());
+        assert_eq!(
+            check_query_limits(&"a".repeat(65), &limits),
+            Err(ValidationError::QueryTooLong {
+                length: 65,
+                limit: 64
+            })
+        );
+        assert_eq!(
+            check_query_limits("{ a b c d e f g h }", &limits),
+            Err(ValidationError::TooManyTokens { limit: 8 })
+        );
+        assert_eq!(
+            check_query_limits("{ a {\n  b { c } } }", &limits),
+            Err(ValidationError::TooDeep {
+                line: 2,
+                column: 5,
+                limit: 2
+            })
+        );
+        let many_tokens = RequestLimits {
+            max_tokens: 100,
+            ..limits.clone()
+        };
+        assert!(check_query_limits("{ a(x: [{ y: [1] }]) { b } }", &many_tokens).is_ok());
+        // Lexing stops at first exceeded limit, later syntax error is not reached
+        assert_eq!(
+            check_query_limits("{ a b c d e f g h ? }", &limits),
+            Err(ValidationError::TooManyTokens { limit: 8 })
+        );
+        assert_eq!(
+            check_query_limits("{ a(x: \"b) }", &limits),
+            Err(ValidationError::Syntax {
+                line: 1,
+                column: 8,
+                message: "Unterminated string".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn variables_size_test() {
+        let variables: BTreeMap<String, Value> = [
+            ("title".to_string(), serde_json::json!("Rocket")),
+            ("cover".to_string(), serde_json::json!("x".repeat(100))),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+
+        assert!(check_variables_size(&variables, 1024).is_ok());
+        assert!(check_variables_size(&BTreeMap::new(), 0).is_ok());
+
+        let error = check_variables_size(&variables, 64).unwrap_err();
+        assert_eq!(
+            error,
+            ValidationError::VariablesTooLarge {
+                name: "cover".to_string(),
+                size: 129,
+                limit: 64,
+                sizes: vec![("cover".to_string(), 102), ("title".to_string(), 8)],
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Variables have 129 bytes which is over limit of 64 bytes, largest is `cover` (cover: 102 bytes, title: 8 bytes)"
+        );
+    }
+}